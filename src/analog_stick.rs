@@ -3,21 +3,31 @@ use arduino_hal::port::mode::{Input, PullUp};
 use arduino_hal::hal::port::{PC0, PC1, PC2};
 use arduino_hal::port::{mode::Analog, Pin};
 
+use crate::common::DEFAULT_DEAD_ZONE;
+use crate::libs::time_util::now;
 use crate::traits::Controller;
 
+use crate::internal_representation::button::ButtonDebouncer;
+use crate::internal_representation::calibration::Calibration;
 use crate::internal_representation::controller_input::ControllerInput;
 use crate::internal_representation::direction::Direction;
-use crate::internal_representation::pin_state::PinState;
+use crate::internal_representation::stick_direction::determine_direction;
 
-const ANALOG_LOWER_THRESHOLD: u16 = 150;
-const ANALOG_UPPER_THRESHOLD: u16 = 850;
+const CALIBRATION_SAMPLES: u16 = 16;
+
+// The dead zone for each level of sensitivity, the default level matches
+// DEFAULT_DEAD_ZONE.
+const SENSITIVITY_DEAD_ZONES: [u8; 5] = [85, 78, DEFAULT_DEAD_ZONE, 60, 50];
 
 pub struct AnalogStick {
     x_pin: Pin<Analog, PC0>,
     y_pin: Pin<Analog, PC1>,
     switch_pin: Pin<Input<PullUp>, PC2>,
-    switch_state: PinState,
+    button: ButtonDebouncer,
     ad_converter: Adc,
+    calibration: Calibration,
+    dead_zone: u8, // percent
+    direction: Direction,
 }
 
 impl<'a> AnalogStick {
@@ -25,14 +35,64 @@ impl<'a> AnalogStick {
             x_pin: Pin<Analog, PC0>,
             y_pin: Pin<Analog, PC1>,
             switch_pin: Pin<Input<PullUp>, PC2>,
-            ad_converter: Adc) -> AnalogStick {
+            ad_converter: Adc,
+            calibration: Calibration) -> AnalogStick {
 
         AnalogStick {
             x_pin,
             y_pin,
             switch_pin,
-            switch_state: PinState::High,
-            ad_converter }
+            button: ButtonDebouncer::new(),
+            ad_converter,
+            calibration,
+            dead_zone: DEFAULT_DEAD_ZONE,
+            direction: Direction::NoDirection }
+    }
+
+    pub fn is_switch_pressed(&self) -> bool {
+        self.switch_pin.is_low()
+    }
+
+    // Samples the readings of the stick in its neutral position, the stick
+    // must be left untouched while this runs.
+    pub fn calibrate(&mut self) -> Calibration {
+        let mut x_sum: u32 = 0;
+        let mut y_sum: u32 = 0;
+        for _ in 0..CALIBRATION_SAMPLES {
+            x_sum += self.x_pin.analog_read(&mut self.ad_converter) as u32;
+            y_sum += self.y_pin.analog_read(&mut self.ad_converter) as u32;
+            arduino_hal::delay_ms(2);
+        }
+
+        self.calibration.center_x = (x_sum / CALIBRATION_SAMPLES as u32) as u16;
+        self.calibration.center_y = (y_sum / CALIBRATION_SAMPLES as u32) as u16;
+        self.calibration
+    }
+
+    // Waits for the stick to be pushed up, which tells the way it is mounted,
+    // and then for it to be let go. The wait is called between the readings,
+    // e.g. to keep the screen refreshed.
+    pub fn orient(&mut self, wait: &mut dyn FnMut()) -> Calibration {
+        loop {
+            let x_value = self.x_pin.analog_read(&mut self.ad_converter);
+            let y_value = self.y_pin.analog_read(&mut self.ad_converter);
+            if self.calibration.orient(x_value, y_value) {
+                break;
+            }
+            wait();
+        }
+
+        loop {
+            let x_value = self.x_pin.analog_read(&mut self.ad_converter);
+            let y_value = self.y_pin.analog_read(&mut self.ad_converter);
+            let (x, y) = self.calibration.displacement(x_value, y_value);
+            let direction = determine_direction(x, y, self.dead_zone, Direction::NoDirection);
+            if direction == Direction::NoDirection {
+                break;
+            }
+            wait();
+        }
+        self.calibration
     }
 }
 
@@ -41,40 +101,28 @@ impl Controller for AnalogStick {
      * The values read from the analog stick range from 0 to 1024 with some
      * minor fluctuations caused by hardware deficiencies. In the neutral
      * position, the two values read from the analog pins of the stick should be
-     * roughly equal to 500. Then a value of 0 on the x_pin would indicate that
-     * the stick has been fully displaced up, whereas a value of 1024 would indicate
-     * that it was fully moved down. The threshold when we detect a choice of
-     * direction is a bit lower to account for the inaccuracies of the measurement
-     * At present the lower threshold is 150 whereas the upper one is 850.
+     * roughly equal to 500, the exact values are sampled during calibration.
+     * Then a value of 0 on the x_pin would indicate that the stick has been
+     * fully displaced up, whereas a value of 1024 would indicate that it was
+     * fully moved down. A direction is only registered once the stick leaves
+     * the dead zone, which is a percentage of the displacement from the centre.
      *
      */
     fn read_input(&mut self) -> ControllerInput {
         let x_value: u16 = self.x_pin.analog_read(&mut self.ad_converter);
         let y_value: u16 = self.y_pin.analog_read(&mut self.ad_converter);
-        let switch_input = PinState::from(self.switch_pin.is_high());
+        let button = self.button.update(self.switch_pin.is_low(), now());
 
-        let toggle_signal = toggle_registered(switch_input, self.switch_state);
-        let direction = determine_direction(x_value, y_value);
+        let (x, y) = self.calibration.displacement(x_value, y_value);
+        let direction = determine_direction(x, y, self.dead_zone, self.direction);
 
-        self.switch_state = switch_input;
+        self.direction = direction;
 
-        ControllerInput::new(toggle_signal, direction)
+        ControllerInput::new(button, direction)
     }
-}
 
-fn toggle_registered(new_state: PinState, old_state: PinState) -> bool {
-    old_state == PinState::High && new_state == PinState::Low
-}
-
-fn determine_direction(x_value: u16, y_value: u16) -> Direction {
-    let mut direction: Direction = Direction::NoDirection;
-
-    if x_value < ANALOG_LOWER_THRESHOLD { direction = Direction::Up; }
-    if x_value > ANALOG_UPPER_THRESHOLD { direction = Direction::Down; }
-    if y_value < ANALOG_LOWER_THRESHOLD { direction = Direction::Right; }
-    if y_value > ANALOG_UPPER_THRESHOLD { direction = Direction::Left; }
-
-    direction
+    fn set_sensitivity(&mut self, level: u8) -> () {
+        let index = (level.max(1) as usize - 1).min(SENSITIVITY_DEAD_ZONES.len() - 1);
+        self.dead_zone = SENSITIVITY_DEAD_ZONES[index];
+    }
 }
-
-
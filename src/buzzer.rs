@@ -0,0 +1,107 @@
+use crate::libs::time::{Instant, Duration};
+use crate::libs::time_util::now;
+use crate::libs::tone::ToneGenerator;
+use crate::traits::{Runnable, Sound};
+
+use crate::internal_representation::melody::Tune;
+use crate::internal_representation::sequencer::Sequencer;
+use crate::internal_representation::sound_effect::{SoundEffect, Tone};
+
+// Plays sound effects and background music on a piezo buzzer without
+// blocking, each call to run_once checks if the current tone or note is over
+// and moves on to the next one. Sound effects take priority over the music,
+// which carries on in the background and is heard again once they end.
+// The sounds are picked by the sound director.
+pub struct Buzzer {
+    tone_generator: ToneGenerator,
+    tones: &'static [Tone],
+    current_tone: usize,
+    tone_started: Instant,
+    sequencer: Sequencer,
+    is_muted: bool,
+}
+
+impl Buzzer {
+    pub fn new(tone_generator: ToneGenerator) -> Buzzer {
+        Buzzer {
+            tone_generator,
+            tones: &[],
+            current_tone: 0,
+            tone_started: Instant::default(),
+            sequencer: Sequencer::new(),
+            is_muted: false,
+        }
+    }
+
+    fn start_tone(&mut self, index: usize) {
+        self.current_tone = index;
+        self.tone_started = now();
+        match self.tones.get(index) {
+            Some(tone) => self.tone_generator.play(tone.frequency),
+            None       => self.resume_music(),
+        }
+    }
+
+    fn is_effect_playing(&self) -> bool {
+        self.current_tone < self.tones.len()
+    }
+
+    fn play_music_note(&mut self, frequency: u16) {
+        if !self.is_muted && !self.is_effect_playing() {
+            self.tone_generator.play(frequency);
+        }
+    }
+
+    fn resume_music(&mut self) {
+        match self.sequencer.get_current_frequency() {
+            Some(frequency) if !self.is_muted => self.tone_generator.play(frequency),
+            _                                 => self.tone_generator.stop(),
+        }
+    }
+}
+
+impl Sound for Buzzer {
+    fn play(&mut self, effect: SoundEffect) -> () {
+        if self.is_muted {
+            return;
+        }
+        self.tones = effect.get_tones();
+        self.start_tone(0);
+    }
+
+    fn play_music(&mut self, tune: Tune, tempo: u16) -> () {
+        if let Some(frequency) = self.sequencer.start(tune.get_melody(), tempo, now()) {
+            self.play_music_note(frequency);
+        }
+    }
+
+    fn stop_music(&mut self) -> () {
+        self.sequencer.stop();
+        if !self.is_effect_playing() {
+            self.tone_generator.stop();
+        }
+    }
+
+    fn set_muted(&mut self, muted: bool) -> () {
+        self.is_muted = muted;
+        self.tones = &[];
+        self.resume_music();
+    }
+}
+
+impl Runnable for Buzzer {
+    fn run_once(&mut self) -> () {
+        if let Some(frequency) = self.sequencer.update(now()) {
+            self.play_music_note(frequency);
+        }
+
+        let tone = match self.tones.get(self.current_tone) {
+            Some(tone) => *tone,
+            None       => return,
+        };
+
+        if now() - self.tone_started >= Duration::from_millis(tone.duration as u32) {
+            self.start_tone(self.current_tone + 1);
+        }
+    }
+}
@@ -1,12 +1,47 @@
+use crate::libs::time::Duration;
+
 pub const BOARD_SIZE: usize = 8;
-pub const MAX_SCORE: usize = BOARD_SIZE * BOARD_SIZE - 1;
 
 // The time between two steps of the snake,
-// effectively controlling the speed of the game. (miliseconds)
-pub const SNAKE_MOVE_INTERVAL_FAST: u32 = 200;
-pub const SNAKE_MOVE_INTERVAL_NORMAL: u32 = 300;
-pub const SNAKE_MOVE_INTERVAL_SLOW: u32 = 400;
+// effectively controlling the speed of the game.
+pub const SNAKE_MOVE_INTERVAL_FAST: Duration = Duration::from_millis(200);
+pub const SNAKE_MOVE_INTERVAL_NORMAL: Duration = Duration::from_millis(300);
+pub const SNAKE_MOVE_INTERVAL_SLOW: Duration = Duration::from_millis(400);
+
+// The percentage of the analog stick displacement from the centre within
+// which no direction is registered.
+pub const DEFAULT_DEAD_ZONE: u8 = 70;
+
+// Once a direction is registered on a diagonal, the other axis needs to be
+// displaced by that many percent more to take over.
+pub const STICK_HYSTERESIS: i16 = 15;
+
+// EEPROM layout, each record takes up its data length plus two bytes.
+pub const CALIBRATION_ADDRESS: u16 = 0x000;
+pub const SETTINGS_ADDRESS: u16 = 0x010;
+pub const BOOT_COUNTER_ADDRESS: u16 = 0x020;
+pub const STATISTICS_ADDRESS: u16 = 0x040;
+
+// The number of brightness levels of the matrix.
+pub const MAX_BRIGHTNESS: u8 = 4;
+
+// Whether the tempo of the music during the game follows the game speed.
+pub const MUSIC_FOLLOWS_GAME_SPEED: bool = true;
+
+// The time after which the console goes to sleep if left alone in the menu.
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+// The time between two updates of the game logic, the snake itself moves
+// at the pace set by the game speed.
+pub const GAME_TICK_INTERVAL: Duration = Duration::from_millis(10);
+
+// The time between two updates of the event observers, e.g. checks whether
+// the next tone or note is due.
+pub const OBSERVERS_UPDATE_INTERVAL: Duration = Duration::from_millis(10);
 
 // The time between calls to controller.get_input(). Controlls the
-// responsiveness of the controlls. (miliseconds)
-pub const CONTROLLER_POLLING_INTERVAL: u32 = 33;
+// responsiveness of the controlls.
+pub const CONTROLLER_POLLING_INTERVAL: Duration = Duration::from_millis(33);
+
+// The time between two reports of the missed deadlines over the serial port.
+pub const TELEMETRY_INTERVAL: Duration = Duration::from_secs(10);
@@ -0,0 +1,160 @@
+use embedded_hal::digital::v2::InputPin;
+
+use crate::libs::time_util::now;
+use crate::traits::Controller;
+
+use crate::internal_representation::button::ButtonDebouncer;
+use crate::internal_representation::controller_input::ControllerInput;
+use crate::internal_representation::direction::Direction;
+
+// Directional pad made of four push buttons and a separate fire button. All
+// buttons are expected to connect their pins to the ground when pressed, so
+// the pins need to be pulled up.
+pub struct DPad<UpPin, DownPin, LeftPin, RightPin, FirePin>
+    where UpPin: InputPin,
+          DownPin: InputPin,
+          LeftPin: InputPin,
+          RightPin: InputPin,
+          FirePin: InputPin,
+{
+    up_pin: UpPin,
+    down_pin: DownPin,
+    left_pin: LeftPin,
+    right_pin: RightPin,
+    fire_pin: FirePin,
+    button: ButtonDebouncer,
+}
+
+impl<UpPin, DownPin, LeftPin, RightPin, FirePin>
+    DPad<UpPin, DownPin, LeftPin, RightPin, FirePin>
+    where UpPin: InputPin,
+          DownPin: InputPin,
+          LeftPin: InputPin,
+          RightPin: InputPin,
+          FirePin: InputPin,
+{
+    pub fn new(
+            up_pin: UpPin,
+            down_pin: DownPin,
+            left_pin: LeftPin,
+            right_pin: RightPin,
+            fire_pin: FirePin) -> Self {
+
+        DPad {
+            up_pin,
+            down_pin,
+            left_pin,
+            right_pin,
+            fire_pin,
+            button: ButtonDebouncer::new() }
+    }
+}
+
+impl<UpPin, DownPin, LeftPin, RightPin, FirePin> Controller
+    for DPad<UpPin, DownPin, LeftPin, RightPin, FirePin>
+    where UpPin: InputPin,
+          DownPin: InputPin,
+          LeftPin: InputPin,
+          RightPin: InputPin,
+          FirePin: InputPin,
+{
+    fn read_input(&mut self) -> ControllerInput {
+        let button = self.button.update(is_pressed(&self.fire_pin), now());
+        let direction = determine_direction(
+            is_pressed(&self.up_pin),
+            is_pressed(&self.down_pin),
+            is_pressed(&self.left_pin),
+            is_pressed(&self.right_pin));
+
+        ControllerInput::new(button, direction)
+    }
+
+    // The buttons are either pressed or not.
+    fn set_sensitivity(&mut self, _level: u8) -> () {}
+}
+
+fn is_pressed<Pin: InputPin>(pin: &Pin) -> bool {
+    pin.is_low().unwrap_or(false)
+}
+
+// If several buttons are held down at once, the first one in the order up,
+// down, left, right wins.
+fn determine_direction(up: bool, down: bool, left: bool, right: bool) -> Direction {
+    if up         { Direction::Up }
+    else if down  { Direction::Down }
+    else if left  { Direction::Left }
+    else if right { Direction::Right }
+    else          { Direction::NoDirection }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::Cell;
+    use std::convert::Infallible;
+    use std::rc::Rc;
+
+    use crate::internal_representation::button::ButtonEvent;
+    use crate::libs::time::Duration;
+    use crate::libs::time_util::advance;
+
+    // A button which is pressed while its shared flag is set.
+    #[derive(Clone, Default)]
+    struct FakeButton(Rc<Cell<bool>>);
+
+    impl InputPin for FakeButton {
+        type Error = Infallible;
+
+        fn is_high(&self) -> Result<bool, Infallible> {
+            Ok(!self.0.get())
+        }
+
+        fn is_low(&self) -> Result<bool, Infallible> {
+            Ok(self.0.get())
+        }
+    }
+
+    fn dpad(buttons: &[FakeButton; 5]) -> DPad<FakeButton, FakeButton, FakeButton, FakeButton, FakeButton> {
+        DPad::new(
+            buttons[0].clone(),
+            buttons[1].clone(),
+            buttons[2].clone(),
+            buttons[3].clone(),
+            buttons[4].clone())
+    }
+
+    #[test]
+    fn first_held_direction_wins() {
+        let buttons: [FakeButton; 5] = Default::default();
+        let mut dpad = dpad(&buttons);
+        assert!(dpad.read_input().direction == Direction::NoDirection);
+
+        buttons[3].0.set(true);
+        assert!(dpad.read_input().direction == Direction::Right);
+        buttons[2].0.set(true);
+        assert!(dpad.read_input().direction == Direction::Left);
+        buttons[0].0.set(true);
+        assert!(dpad.read_input().direction == Direction::Up);
+    }
+
+    #[test]
+    fn fire_button_is_debounced_into_gestures() {
+        let buttons: [FakeButton; 5] = Default::default();
+        let mut dpad = dpad(&buttons);
+        let mut events = Vec::new();
+        let mut poll = |dpad: &mut DPad<_, _, _, _, _>, pressed: bool, millis: u32| {
+            buttons[4].0.set(pressed);
+            for _ in 0..millis / 10 {
+                advance(Duration::from_millis(10));
+                events.extend(dpad.read_input().button);
+            }
+        };
+
+        poll(&mut dpad, false, 100);
+        poll(&mut dpad, true, 100);
+        poll(&mut dpad, false, 500);
+
+        assert!(events == [ButtonEvent::Press, ButtonEvent::Release, ButtonEvent::ShortClick]);
+    }
+}
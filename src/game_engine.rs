@@ -1,108 +1,378 @@
+use core::cell::RefCell;
+
 use oorandom::Rand32;
 
-use crate::common::BOARD_SIZE;
-use crate::libs::time_util::millis;
+use crate::common::{BOARD_SIZE, IDLE_TIMEOUT};
+use crate::libs::ring_buffer::RingBuffer;
+use crate::libs::time::{Instant, Duration};
+use crate::libs::time_util::{now, micros};
 use crate::traits::{Runnable, ControllerListener, View};
-use crate::user_interface as UI;
+use crate::user_interface::{self as UI, MenuWidget, MenuChange, StatisticsBrowser};
+use crate::user_interface::{CodeEntry, CodeEntryChange};
 
 use crate::internal_representation::controller_input::ControllerInput;
-use crate::internal_representation::game_state::{GameState, OperationMode, GameSpeed};
-use crate::internal_representation::game_board::{GameBoard, BoardCell};
+use crate::internal_representation::game_state::{GameState, OperationMode, GameMode, GameSpeed};
+use crate::internal_representation::game_board::{GameBoard, BoardCell, BLINK_PHASES};
 use crate::internal_representation::map::Map;
 use crate::internal_representation::snake::Snake;
 use crate::internal_representation::point::Point;
-use crate::internal_representation::direction::Direction;
+use crate::internal_representation::direction::{Direction, Turn};
+use crate::internal_representation::turn_queue::TurnQueue;
+use crate::internal_representation::game_event::{GameEvent, DeathCause};
+use crate::internal_representation::state_machine::{self, Trigger};
+use crate::internal_representation::settings::Settings;
+use crate::internal_representation::rules::{Rules, WallMode};
+use crate::internal_representation::apple_placement::{self, ApplePlacement, AppleSequence};
+use crate::internal_representation::item::{self, Item};
+use crate::internal_representation::hazard::Hazards;
+use crate::internal_representation::scoring::Scoring;
+use crate::internal_representation::statistics::Statistics;
+use crate::internal_representation::entropy::EntropyPool;
+use crate::internal_representation::challenge::Challenge;
+use crate::internal_representation::idle_timer::IdleTimer;
+use crate::internal_representation::frame::Frame;
+
+// The time after waking up during which the button events are ignored, so
+// that the press which woke the console up doesn't start a game.
+const WAKE_UP_GUARD: Duration = Duration::from_secs(1);
+
+// The numbers counted down before the game starts and the time for which
+// each of them is shown.
+const COUNTDOWN_FROM: u8 = 3;
+const COUNTDOWN_STEP: Duration = Duration::from_millis(700);
+
+// The time for which the final score is shown before returning to the menu.
+const GAME_OVER_DURATION: Duration = Duration::from_secs(3);
+
+// The time a time attack lasts and the time lost each time the snake dies
+// during one.
+const TIME_ATTACK_DURATION: Duration = Duration::from_secs(60);
+const TIME_ATTACK_DEATH_PENALTY: Duration = Duration::from_secs(5);
+
+// The time each phase of the blink cycle of the items lasts.
+const BLINK_STEP: Duration = Duration::from_millis(125);
+
+// The events are picked up after each run of the engine, a single run emits
+// at most a handful of them.
+const MAX_PENDING_EVENTS: usize = 8;
 
 pub struct GameEngine<'a> {
     state: GameState,
+    scoring: Scoring,
     board: GameBoard,
     snake: Snake,
+    turns: TurnQueue,
     map: Map,
+    // The cell of the special item on the board and the time it disappears at.
+    placed_item: Option<(Point, Instant)>,
+    hazards: Hazards,
     generator: Rand32,
+    // The generator is seeded anew from the pool for each game.
+    entropy: EntropyPool,
     controller_input: ControllerInput,
+    menu_cursor: Direction,
+    settings: Settings,
+    rules: Rules,
+    settings_menu: MenuWidget,
+    // The statistics are kept up to date by an observer, the engine only
+    // shows them.
+    statistics: &'a RefCell<Statistics>,
+    statistics_browser: StatisticsBrowser,
+    code_entry: CodeEntry,
+    // The challenge played last, it sets up the game while it is played.
+    challenge: Challenge,
+    // The cells the apples of a challenge go to, in order.
+    apple_sequence: AppleSequence,
+    is_direction_held_over: bool,
+    idle_timer: IdleTimer,
+    buttons_ignored_until: Instant,
+    events: RingBuffer<GameEvent, MAX_PENDING_EVENTS>,
     view: &'a mut dyn View,
 }
 
 impl<'a> Runnable for GameEngine<'a> {
     fn run_once(&mut self) -> () {
+        if let Some(trigger) = self.take_input_trigger() {
+            self.fire(trigger);
+            return;
+        }
+
         match self.state.mode {
-            OperationMode::GameRunning  => self.run_game(),
-            OperationMode::InMenu       => self.run_menu(),
-            OperationMode::SelectingMap => self.run_map_menu(),
+            OperationMode::Boot       => self.fire(Trigger::BootFinished),
+            OperationMode::Menu       => self.run_menu(),
+            OperationMode::MapSelect  => self.run_map_menu(),
+            OperationMode::Countdown  => self.run_countdown(),
+            OperationMode::Playing    => self.run_game(),
+            OperationMode::GameOver   => self.run_game_over(),
+            OperationMode::Settings   => self.run_settings(),
+            OperationMode::HighScores => self.show_best_scores(),
+            OperationMode::Statistics => self.run_statistics(),
+            OperationMode::CodeEntry  => self.run_code_entry(),
+            OperationMode::Paused     => (),
         }
-        self.view.run_once();
     }
 }
 
 impl<'a> ControllerListener for GameEngine<'a> {
-    fn on_input(&mut self, input: ControllerInput) {
+    fn on_input(&mut self, mut input: ControllerInput) {
+        let now = now();
+        if input.is_active() {
+            self.idle_timer.register_activity(now);
+            // The exact timing of the player's input is hard to predict.
+            self.entropy.mix(micros());
+        }
+
+        if now < self.buttons_ignored_until {
+            input.reset_button();
+        }
+
+        // After the mode changes, the stick needs to go back to the centre
+        // before its direction counts again, so that holding it doesn't go
+        // through several modes in a row.
+        if self.is_direction_held_over {
+            match input.direction {
+                Direction::NoDirection => self.is_direction_held_over = false,
+                _                      => input.direction = Direction::NoDirection,
+            }
+        }
+
+        // The first turn can be queued during the countdown already.
         match self.state.mode {
-            OperationMode::InMenu => self.controller_input = input,
-            _                     => self.override_direction_if_set(input),
+            OperationMode::Playing    |
+            OperationMode::Countdown  => self.queue_turn(input),
+            OperationMode::Menu       |
+            OperationMode::Settings   |
+            OperationMode::Statistics |
+            OperationMode::CodeEntry  => {
+                self.controller_input = self.resolve_menu_turn(input)
+            },
+            OperationMode::MapSelect  => {
+                let input = self.resolve_menu_turn(input);
+                self.override_direction_if_set(input)
+            },
+            _                         => self.controller_input = input,
         }
     }
 }
 
 impl<'a> GameEngine<'a> {
-    pub fn new(view: &'a mut dyn View, seed: u16) -> GameEngine {
-        GameEngine {
+    pub fn new(
+        view: &'a mut dyn View,
+        settings: Settings,
+        statistics: &'a RefCell<Statistics>,
+        mut entropy: EntropyPool) -> GameEngine<'a> {
+
+        let mut engine = GameEngine {
             state: GameState::new(),
+            scoring: Scoring::new(),
             board: GameBoard::default(),
-            snake: Snake::new(),
+            snake: Snake::new(settings.rules().starting_length),
+            turns: TurnQueue::new(),
             map: Map::new(),
-            generator: oorandom::Rand32::new(seed as u64),
+            placed_item: None,
+            hazards: Hazards::new(&[]),
+            generator: Rand32::new(entropy.draw_seed()),
+            entropy,
             controller_input: ControllerInput::default(),
+            menu_cursor: Direction::Up,
+            settings,
+            rules: settings.rules(),
+            settings_menu: MenuWidget::new(),
+            statistics,
+            statistics_browser: StatisticsBrowser::new(),
+            code_entry: CodeEntry::new(),
+            challenge: Challenge::default(),
+            apple_sequence: Challenge::default().apple_sequence(),
+            is_direction_held_over: false,
+            idle_timer: IdleTimer::new(IDLE_TIMEOUT, now()),
+            buttons_ignored_until: Instant::default(),
+            events: RingBuffer::new(),
             view,
+        };
+        engine.apply_settings();
+        engine.emit(GameEvent::WokeUp);
+        engine
+    }
+
+    pub fn refresh_display(&mut self) {
+        self.view.run_once();
+    }
+
+    // Returns the oldest event which hasn't been picked up yet.
+    pub fn poll_event(&mut self) -> Option<GameEvent> {
+        self.events.pop_front()
+    }
+
+    // The console only goes to sleep when left alone in the menus.
+    pub fn is_idle(&self) -> bool {
+        match self.state.mode {
+            OperationMode::Boot      |
+            OperationMode::Countdown |
+            OperationMode::Playing   |
+            OperationMode::Paused    => false,
+            _                        => self.idle_timer.is_idle(now()),
         }
     }
 
-    fn run_game(&mut self) {
-        if self.controller_input.toggle_signal {
-           self.end_game();
-           return;
+    pub fn go_to_sleep(&mut self) {
+        self.view.update(Frame::default());
+        self.view.run_once();
+        self.emit(GameEvent::WentToSleep);
+    }
+
+    // The state of the menu and the settings is kept intact while sleeping.
+    pub fn wake_up(&mut self) {
+        let now = now();
+        self.idle_timer.register_activity(now);
+        self.buttons_ignored_until = now + WAKE_UP_GUARD;
+        self.controller_input = ControllerInput::default();
+        self.emit(GameEvent::WokeUp);
+    }
+
+    // Looks up the trigger bound to the button event or the direction, the
+    // input which fires a trigger is used up.
+    fn take_input_trigger(&mut self) -> Option<Trigger> {
+        let mode = self.state.mode;
+
+        let button_trigger = self.controller_input.button
+            .and_then(|button| state_machine::button_trigger(mode, button));
+        if button_trigger.is_some() {
+            self.controller_input.reset_button();
+            return button_trigger;
         }
 
-        if self.state.is_time_for_next_move() {
-            self.state.register_move_at(millis());
-            self.make_move();
-            self.view.update(self.board.get_screen());
+        let direction_trigger =
+            state_machine::direction_trigger(mode, self.controller_input.direction);
+        if direction_trigger.is_some() {
+            self.controller_input.direction = Direction::NoDirection;
         }
+        direction_trigger
     }
 
-    fn run_menu(&mut self) {
-        if self.controller_input.toggle_signal {
-            self.start_game();
-            self.controller_input.reset_signal();
-            return;
+    // Moves to the mode the trigger leads to, if any, running the exit action
+    // of the current mode and the entry action of the next one.
+    fn fire(&mut self, trigger: Trigger) {
+        let from = self.state.mode;
+        let to = match state_machine::next_mode(from, trigger) {
+            Some(to) => to,
+            None     => return,
+        };
+
+        match trigger {
+            Trigger::Start           => self.state.game_mode = GameMode::Classic,
+            Trigger::StartTimeAttack => self.state.game_mode = GameMode::TimeAttack,
+            Trigger::StartChallenge  => {
+                self.state.game_mode = GameMode::Challenge;
+                self.challenge = Challenge::new(self.code_entry.code());
+            },
+            _                        => (),
         }
 
-        match self.controller_input.direction {
-            Direction::Left        => self.select_map(),
-            Direction::Up          => self.set_speed(GameSpeed::Slow),
-            Direction::Right       => self.set_speed(GameSpeed::Normal),
-            Direction::Down        => self.set_speed(GameSpeed::Fast),
-            Direction::NoDirection => self.print_score(),
+        self.exit_mode(from);
+        self.state.enter_mode(to);
+        self.controller_input = ControllerInput::default();
+        self.is_direction_held_over = true;
+        self.emit(GameEvent::ModeChanged { from, to });
+        self.enter_mode(to);
+    }
+
+    fn enter_mode(&mut self, mode: OperationMode) {
+        match mode {
+            OperationMode::Boot       => (),
+            OperationMode::Menu       => (), // The menu screen depends on the input.
+            OperationMode::MapSelect  => self.view.update(UI::print_up_down_arrows()),
+            OperationMode::Countdown  => self.start_game(),
+            OperationMode::Playing    => {
+                // The time spent in the countdown or paused doesn't count.
+                self.state.register_move_at(now());
+                self.show_board();
+            },
+            OperationMode::Paused     => self.view.update(UI::print_pause()),
+            OperationMode::GameOver   => {
+                self.state.record_score();
+                self.emit(GameEvent::GameEnded { longest_snake: self.state.longest_snake });
+                self.show_final_score();
+            },
+            OperationMode::HighScores => self.show_best_scores(),
+            OperationMode::Settings   => {
+                self.settings_menu.stop_editing();
+                self.view.update(self.settings_menu.render(&self.settings));
+            },
+            OperationMode::Statistics => {
+                self.statistics_browser.restart(now());
+                self.show_statistics();
+            },
+            OperationMode::CodeEntry  => {
+                self.code_entry.restart();
+                self.view.update(self.code_entry.render());
+            },
         }
     }
 
-    fn run_map_menu(&mut self) {
-        if self.controller_input.toggle_signal {
-            self.start_game();
-            self.controller_input.reset_signal();
+    fn exit_mode(&mut self, mode: OperationMode) {
+        match mode {
+            OperationMode::GameOver  => self.end_game(),
+            OperationMode::Countdown |
+            OperationMode::Paused    => self.postpone_timers(self.state.time_in_mode()),
+            _                        => (),
+        }
+    }
+
+    // The board is shown on every run rather than after each move only, so
+    // that the items keep blinking.
+    fn run_game(&mut self) {
+        self.expire_item();
+        self.update_hazards();
+        if self.state.mode != OperationMode::Playing {
             return;
         }
 
+        if self.state.game_mode == GameMode::TimeAttack &&
+           now() >= self.state.time_attack_ends_at {
+            self.emit(GameEvent::TimeUp);
+            return self.fire(Trigger::TimeUp);
+        }
+
+        if self.state.is_time_for_next_move() {
+            self.state.register_move_at(now());
+            self.make_move();
+        }
+
+        if self.state.mode == OperationMode::Playing {
+            self.show_board();
+        }
+    }
+
+    // During a time attack, the bottom row flashes the time left once per
+    // blink cycle.
+    fn show_board(&mut self) {
+        let now = now();
+        let steps = now.as_millis() / BLINK_STEP.as_millis();
+        let blink_phase = (steps % BLINK_PHASES as u32) as u8;
+
+        let mut screen = self.board.get_screen(blink_phase);
+        if self.state.game_mode == GameMode::TimeAttack && blink_phase == 0 {
+            let time_left = self.state.time_attack_ends_at.duration_since(now);
+            screen = UI::print_time_bar(screen, time_left, TIME_ATTACK_DURATION);
+        }
+        self.view.update(screen);
+    }
+
+    fn run_menu(&mut self) {
+        self.print_score();
+    }
+
+    fn run_map_menu(&mut self) {
         if !self.map.is_time_for_interaction() {
             return;
         }
 
-        self.map.register_interaction_at(millis());
+        self.map.register_interaction_at(now());
 
         match self.controller_input.direction {
-            Direction::Right => self.state.return_to_menu(),
-            Direction::Up    => self.map.get_next(),
-            Direction::Down  => self.map.get_previous(),
-            _                => (),
+            Direction::Up   => self.change_map(Map::get_next),
+            Direction::Down => self.change_map(Map::get_previous),
+            _               => (),
         }
 
         self.controller_input = ControllerInput::default();
@@ -111,102 +381,519 @@ impl<'a> GameEngine<'a> {
         self.view.update(self.map.print_current_map());
     }
 
+    fn run_countdown(&mut self) {
+        let steps_passed = self.state.time_in_mode().as_millis()
+            / COUNTDOWN_STEP.as_millis();
+
+        if steps_passed >= COUNTDOWN_FROM as u32 {
+            self.fire(Trigger::CountdownFinished);
+        } else {
+            self.view.update(UI::print_digit(COUNTDOWN_FROM - steps_passed as u8));
+        }
+    }
+
+    // Long scores get to scroll through the screen once at least.
+    fn run_game_over(&mut self) {
+        let scroll_duration = match self.state.game_mode {
+            GameMode::Challenge => {
+                UI::challenge_result_scroll_duration(self.challenge.code(), self.state.score)
+            },
+            _                   => UI::score_scroll_duration(self.state.score),
+        };
+        let duration = GAME_OVER_DURATION.max(scroll_duration);
+        if self.state.time_in_mode() >= duration {
+            self.fire(Trigger::Timeout);
+        } else {
+            self.show_final_score();
+        }
+    }
+
+    fn run_settings(&mut self) {
+        let change = self.settings_menu.handle_input(&self.controller_input, &mut self.settings);
+        self.controller_input.reset_button();
+
+        match change {
+            Some(MenuChange::ValueChanged) => self.apply_settings(),
+            Some(_)                        => (),
+            None                           => return,
+        }
+
+        self.emit(GameEvent::MenuNavigated);
+        self.view.update(self.settings_menu.render(&self.settings));
+    }
+
+    fn run_code_entry(&mut self) {
+        let change = self.code_entry.handle_input(&self.controller_input);
+        self.controller_input.reset_button();
+
+        match change {
+            Some(CodeEntryChange::Entered) => return self.fire(Trigger::StartChallenge),
+            Some(_)                        => self.emit(GameEvent::MenuNavigated),
+            None                           => return,
+        }
+        self.view.update(self.code_entry.render());
+    }
+
+    fn run_statistics(&mut self) {
+        if self.statistics_browser.handle_input(&self.controller_input, now()) {
+            self.emit(GameEvent::MenuNavigated);
+        }
+        self.show_statistics();
+    }
+
+    // The games played are shown for the selected map at the current speed.
+    fn show_statistics(&mut self) {
+        let screen = self.statistics_browser.render(
+            &self.statistics.borrow(),
+            self.map.get_current_index(),
+            self.state.game_speed,
+            now());
+        self.view.update(screen);
+    }
+
+    // Hands the current settings over to everything that depends on them,
+    // the observers also get to persist them.
+    fn apply_settings(&mut self) {
+        self.change_speed(self.settings.game_speed());
+        self.rules = self.settings.rules();
+        self.view.set_brightness(self.settings.brightness());
+        self.emit(GameEvent::SettingsChanged { settings: self.settings });
+    }
+
+    fn change_speed(&mut self, speed: GameSpeed) {
+        if self.state.game_speed != speed {
+            self.state.game_speed = speed;
+            self.emit(GameEvent::SpeedChanged { speed });
+        }
+    }
+
     fn start_game(&mut self) {
+        match self.state.game_mode {
+            GameMode::Challenge => self.set_up_challenge(),
+            _                   => self.generator = Rand32::new(self.entropy.draw_seed()),
+        }
         self.board = GameBoard::new(self.map.get_current_map());
-        self.board.add_snake_segment(self.snake.head);
+        self.snake = Snake::new(self.rules.starting_length);
+        for segment in self.snake.segments() {
+            self.board.add_snake_segment(segment);
+        }
+        self.hazards = Hazards::new(self.map.get_hazards());
+        self.update_hazards();
         self.spawn_apple();
+        self.placed_item = None;
+        self.turns.clear();
         self.state.start();
-        self.view.update(self.board.get_screen());
+        self.state.longest_snake = self.snake.len() as u8;
+        self.state.time_attack_ends_at = now() + TIME_ATTACK_DURATION;
+        self.scoring.start(self.state.game_speed, self.map.get_difficulty());
+        self.emit(GameEvent::GameStarted {
+            map_index: self.map.get_current_index(),
+            speed: self.state.game_speed,
+        });
+    }
+
+    // The challenge picks the map, the speed and the rules in place of the
+    // player. The map stays selected afterwards, the speed and the rules go
+    // back to the settings once the game is over.
+    fn set_up_challenge(&mut self) {
+        let challenge = self.challenge;
+        self.generator = Rand32::new(challenge.seed());
+        self.apple_sequence = challenge.apple_sequence();
+        self.map.select(challenge.map_index());
+        self.change_speed(challenge.game_speed());
+        self.rules = challenge.rules();
     }
 
     fn end_game(&mut self) {
-        self.snake = Snake::new();
+        self.turns.clear();
         self.board.reset();
-        self.state.return_to_menu();
-        self.controller_input = ControllerInput::default();
+        if self.state.game_mode == GameMode::Challenge {
+            self.change_speed(self.settings.game_speed());
+            self.rules = self.settings.rules();
+        }
+    }
+
+    // The code of a challenge goes along with the score, so that the results
+    // can be compared.
+    fn show_final_score(&mut self) {
+        let score = self.state.score;
+        let time_shown = self.state.time_in_mode();
+        let screen = match self.state.game_mode {
+            GameMode::Challenge    => {
+                UI::print_challenge_result(self.challenge.code(), score, time_shown)
+            },
+            _ if self.state.is_won => UI::print_trophy(),
+            _                      => UI::print_score(score, time_shown),
+        };
+        self.view.update(screen);
+    }
+
+    fn show_best_scores(&mut self) {
+        let screen = UI::print_best_scores(self.state.best_scores(), self.state.time_in_mode());
+        self.view.update(screen);
     }
 
     fn make_move(&mut self) {
-        self.snake.change_direction(self.controller_input.direction);
+        if let Some(turn) = self.turns.pop() {
+            self.snake.change_direction(turn);
+        }
+
+        let is_ghost = self.state.ghost_moves_left > 0;
+        if is_ghost {
+            self.state.ghost_moves_left -= 1;
+        }
 
-        let cell_ahead = self.snake.look_ahead();
+        let mut cell_ahead = self.snake.look_ahead();
 
         if !self.board.is_within_bounds(cell_ahead) {
-            self.use_grace();
-            return;
+            match self.rules.wall_mode {
+                WallMode::Wrap  => cell_ahead = self.board.wrap(cell_ahead),
+                WallMode::Solid => return self.use_grace(DeathCause::Wall),
+            }
         }
 
         match self.board.read_board_at(cell_ahead) {
-            BoardCell::Empty                       => self.move_snake_forward(),
-            BoardCell::Apple                       => self.eat_apple(),
-            BoardCell::Snake | BoardCell::Obstacle => self.use_grace(),
+            BoardCell::Empty      => self.move_snake_forward(cell_ahead),
+            BoardCell::Apple      => self.eat_apple(cell_ahead),
+            BoardCell::Item(item) => self.collect_item(cell_ahead, item),
+            BoardCell::Snake if is_ghost || self.is_tail_leaving(cell_ahead) => {
+                self.move_snake_forward(cell_ahead)
+            },
+            BoardCell::Snake      => self.use_grace(DeathCause::Snake),
+            BoardCell::Obstacle   => self.use_grace(DeathCause::Obstacle),
         };
     }
 
-    fn use_grace(&mut self) {
-        if self.state.is_grace_pending {
-            self.end_game();
+    fn use_grace(&mut self, cause: DeathCause) {
+        if self.state.grace_moves_used >= self.rules.grace_moves {
+            self.die(cause);
+        } else {
+            self.state.grace_moves_used += 1;
+            self.emit(GameEvent::GraceUsed);
+        }
+    }
+
+    // Unless the tail is about to leave its cell, entering the snake is a
+    // collision.
+    fn is_tail_leaving(&self, cell: Point) -> bool {
+        self.rules.is_tail_chasing_allowed &&
+        self.state.pending_growth == 0 &&
+        self.snake.tail() == cell
+    }
+
+    // The tail moves before the head, so that the head may take over its cell.
+    // While the snake is growing, we don't erase the cell occupied by the
+    // snake's tail which effectively makes the snake longer.
+    fn move_snake_forward(&mut self, head: Point) {
+        if self.state.pending_growth > 0 {
+            self.state.pending_growth -= 1;
         } else {
-            self.state.is_grace_pending = true;
+            self.drop_tail();
         }
+        self.move_snake_head(head);
     }
 
-    fn move_snake_forward(&mut self) {
-        self.move_snake_head();
-        self.board.erase_entry(self.snake.advance_tail());
+    // After passing through itself as a ghost, the snake may still cover the
+    // cell of the tail with another segment.
+    fn drop_tail(&mut self) {
+        let tail = self.snake.advance_tail();
+        if !self.snake.occupies(tail) {
+            self.board.erase_entry(tail);
+        }
     }
 
-    // When eating an apple, we don't erase the cell occupied by the snake's
-    // tail which effectively makes the snake grow.
-    fn eat_apple(&mut self) {
-        self.move_snake_head();
-        self.state.score += 1;
+    fn eat_apple(&mut self, head: Point) {
+        self.swallow_apple();
+        self.move_snake_forward(head);
 
-        if self.state.score as usize == self.map.get_max_score() {
-            // Terminate if the player wins by filling the whole board.
-            return self.end_game();
+        let points = self.scoring.apple_points(1, now());
+        self.add_points(points);
+        if self.reach_goal() {
+            return;
         }
 
-        self.spawn_apple();
+        match self.spawn_apple() {
+            ApplePlacement::At(_)     => self.emit(GameEvent::AppleEaten),
+            ApplePlacement::BoardFull => return self.win(), // The snake filled the whole board.
+        }
+        self.spawn_item();
+    }
+
+    fn collect_item(&mut self, head: Point, item: Item) {
+        self.placed_item = None;
+        self.emit(GameEvent::ItemCollected { item });
+
+        if item == Item::GoldenApple {
+            self.swallow_apple();
+        }
+        self.move_snake_forward(head);
+
+        let points = match item {
+            Item::GoldenApple => self.scoring.apple_points(item::GOLDEN_APPLE_WORTH, now()),
+            _                 => self.scoring.item_points(),
+        };
+        self.add_points(points);
+
+        match item {
+            Item::GoldenApple => { self.reach_goal(); },
+            Item::ShrinkPill  => self.shrink(item::SHRINK_SEGMENTS),
+            Item::SlowMotion  => {
+                self.state.slow_motion_until = now() + item::SLOW_MOTION_DURATION
+            },
+            Item::Ghost       => self.state.ghost_moves_left = item::GHOST_MOVES,
+        }
     }
 
-    fn move_snake_head(&mut self) {
+    fn swallow_apple(&mut self) {
+        self.state.apples_eaten = self.state.apples_eaten.saturating_add(1);
+        self.state.pending_growth =
+            self.state.pending_growth.saturating_add(self.rules.growth_per_apple);
+    }
+
+    // The pill never takes away the head.
+    fn shrink(&mut self, segments: u8) {
+        for _ in 0..segments {
+            if self.snake.len() <= 1 {
+                break;
+            }
+            self.drop_tail();
+        }
+    }
+
+    fn add_points(&mut self, points: u16) {
+        self.state.score = self.state.score.saturating_add(points);
+    }
+
+    // Wins the game once the player has eaten the apples the rules ask for,
+    // returns whether they did.
+    fn reach_goal(&mut self) -> bool {
+        // A time attack goes on until the time is up.
+        let is_goal_reached = self.state.game_mode != GameMode::TimeAttack &&
+            self.rules.is_goal_reached(self.state.apples_eaten);
+        if is_goal_reached {
+            self.win();
+        }
+        is_goal_reached
+    }
+
+    fn win(&mut self) {
+        self.state.is_won = true;
+        self.emit(GameEvent::Won);
+        self.fire(Trigger::Won);
+    }
+
+    fn move_snake_head(&mut self, head: Point) {
         self.state.reset_grace();
-        self.snake.move_forward();
+        self.snake.move_to(head);
         self.board.add_snake_segment(self.snake.head);
+        self.state.longest_snake = self.state.longest_snake.max(self.snake.len() as u8);
     }
 
-    fn spawn_apple(&mut self) {
-        loop {
-            let apple_x = self.generator.rand_range(0..BOARD_SIZE as u32) as i8;
-            let apple_y = self.generator.rand_range(0..BOARD_SIZE as u32) as i8;
+    // An item on the last free cell makes way for the apple, the board isn't
+    // full until the snake covers it.
+    fn spawn_apple(&mut self) -> ApplePlacement {
+        let avoided = self.hazards.reach();
+        let mut placement = match self.state.game_mode {
+            GameMode::Challenge => self.apple_sequence.place_apple(&self.board, avoided),
+            _                   => apple_placement::place_apple(
+                &self.board, self.snake.head, avoided, &self.rules, &mut self.generator),
+        };
 
-            let point = Point::new(apple_x, apple_y);
+        if placement == ApplePlacement::BoardFull {
+            if let Some((point, _)) = self.placed_item.take() {
+                placement = ApplePlacement::At(point);
+            }
+        }
+        if let ApplePlacement::At(point) = placement {
+            self.board.add_apple(point);
+        }
+        placement
+    }
 
-            if self.board.read_board_at(point) == BoardCell::Empty {
-                self.board.add_apple(point);
-                break;
+    // Items only appear one at a time, in the cells an apple could go to.
+    fn spawn_item(&mut self) {
+        if self.placed_item.is_some() {
+            return;
+        }
+
+        let roll = self.generator.rand_range(0..100) as u8;
+        let item = match self.rules.item_odds.pick(roll) {
+            Some(item) => item,
+            None       => return,
+        };
+
+        let placement = apple_placement::place_apple(
+            &self.board, self.snake.head, self.hazards.reach(), &self.rules, &mut self.generator);
+
+        if let ApplePlacement::At(point) = placement {
+            self.board.update_board_entry(point, BoardCell::Item(item));
+            self.placed_item = Some((point, now() + item.lifetime()));
+        }
+    }
+
+    fn expire_item(&mut self) {
+        if let Some((point, expires_at)) = self.placed_item {
+            if now() >= expires_at {
+                self.board.erase_entry(point);
+                self.placed_item = None;
             }
         }
     }
 
+    // The time spent in the countdown or paused doesn't count towards the
+    // items, their effects and the moving hazards. A slow motion which had
+    // already run out when the pause began stays over.
+    fn postpone_timers(&mut self, pause: Duration) {
+        if let Some((_, expires_at)) = self.placed_item.as_mut() {
+            *expires_at += pause;
+        }
+        if now() - pause < self.state.slow_motion_until {
+            self.state.slow_motion_until += pause;
+        }
+        self.state.time_attack_ends_at += pause;
+        self.hazards.postpone(pause);
+        self.scoring.postpone(pause);
+    }
+
+    // Moves the hazards over the board, the cells they leave are freed unless
+    // they belong to the layout of the map. A hazard running into the snake
+    // kills it straight away.
+    fn update_hazards(&mut self) {
+        let previous = self.hazards.cells();
+        if !self.hazards.update(self.state.apples_eaten) {
+            return;
+        }
+
+        let current = self.hazards.cells();
+        let layout = self.map.get_current_map();
+        let mut is_snake_hit = false;
+
+        for y in 0..BOARD_SIZE {
+            for x in 0..BOARD_SIZE {
+                let point = Point::new(x as i8, y as i8);
+                let cell = self.board.read_board_at(point);
+
+                match (previous.is_lit(x, y), current.is_lit(x, y)) {
+                    (true, false) if cell == BoardCell::Obstacle && !layout.is_lit(x, y) => {
+                        self.board.erase_entry(point)
+                    },
+                    (false, true) if cell == BoardCell::Snake => is_snake_hit = true,
+                    (false, true) => {
+                        self.board.update_board_entry(point, BoardCell::Obstacle)
+                    },
+                    _ => (),
+                }
+            }
+        }
+
+        if is_snake_hit {
+            self.die(DeathCause::Obstacle);
+        }
+    }
+
+    // A death ends a classic game or a challenge, during a time attack it
+    // costs time.
+    fn die(&mut self, cause: DeathCause) {
+        self.emit(GameEvent::Died { cause });
+        match self.state.game_mode {
+            GameMode::Classic    |
+            GameMode::Challenge  => self.fire(Trigger::Died),
+            GameMode::TimeAttack => self.respawn(),
+        }
+    }
+
+    // The snake starts over from where it started the game, an apple or an
+    // item underneath it is lost and the apple needs to go somewhere else.
+    // A hazard which ran into the snake takes over the cell it hit.
+    fn respawn(&mut self) {
+        let hazard_cells = self.hazards.cells();
+        for segment in self.snake.segments() {
+            let cell = match hazard_cells.is_lit(segment.x as usize, segment.y as usize) {
+                true  => BoardCell::Obstacle,
+                false => BoardCell::Empty,
+            };
+            self.board.update_board_entry(segment, cell);
+        }
+
+        self.snake = Snake::new(self.rules.starting_length);
+        let mut is_apple_covered = false;
+        for segment in self.snake.segments() {
+            match self.board.read_board_at(segment) {
+                BoardCell::Apple   => is_apple_covered = true,
+                BoardCell::Item(_) => self.placed_item = None,
+                _                  => (),
+            }
+            self.board.add_snake_segment(segment);
+        }
+
+        if is_apple_covered {
+            self.spawn_apple();
+        }
+
+        self.turns.clear();
+        self.state.reset_grace();
+        self.state.pending_growth = 0;
+        self.state.ghost_moves_left = 0;
+        self.state.time_attack_ends_at =
+            self.state.time_attack_ends_at - TIME_ATTACK_DEATH_PENALTY;
+    }
+
+    // When in game we don't act on the direction immediately. Instead, each
+    // turn is queued and the snake consumes one of them per move, so that
+    // quick consecutive turns between two steps aren't lost.
+    fn queue_turn(&mut self, input: ControllerInput) {
+        self.controller_input.button = input.button;
+
+        let current_direction = self.snake.get_direction();
+        match input.turn {
+            Some(turn) => self.turns.push_relative(turn, current_direction),
+            None       => self.turns.push(input.direction, current_direction),
+        }
+    }
+
+    // Controllers which only turn left or right can't point at the menu
+    // options directly. In the main menu, each turn moves a cursor through the
+    // options clockwise or anticlockwise, whereas in the map menu, the
+    // statistics and the code entry turns flip through the maps, the
+    // statistics and the digits.
+    fn resolve_menu_turn(&mut self, mut input: ControllerInput) -> ControllerInput {
+        let turn = match input.turn {
+            Some(turn) => turn,
+            None       => return input,
+        };
+
+        input.direction = match self.state.mode {
+            OperationMode::MapSelect  |
+            OperationMode::Statistics |
+            OperationMode::CodeEntry  => match turn {
+                Turn::Left  => Direction::Up,
+                Turn::Right => Direction::Down,
+            },
+            _ => {
+                self.menu_cursor = self.menu_cursor.turned(turn);
+                self.menu_cursor
+            },
+        };
+        input
+    }
+
     fn override_direction_if_set(&mut self, input: ControllerInput) {
-        self.controller_input.toggle_signal = input.toggle_signal;
+        self.controller_input.button = input.button;
 
-        // When in game we don't overwrite the direction if it is not set
-        // because we don't want to lose user input between steps, we want to
-        // read in the direction selection once per snake move and have it
-        // persist even if during the subsequent analog stick readings during
-        // that move the controller returns NoDirection.
+        // When selecting the map we don't overwrite the direction if it is not
+        // set because we don't want to lose user input between interactions,
+        // we want to read in the direction selection once per interaction and
+        // have it persist even if during the subsequent analog stick readings
+        // the controller returns NoDirection.
         match input.direction {
             Direction::NoDirection => (), // Don't override when no input
             _ => self.controller_input.direction = input.direction,
         };
     }
 
-    fn select_map(&mut self) {
-        self.state.mode = OperationMode::SelectingMap;
-        self.view.update(UI::print_up_down_arrows());
+    fn change_map(&mut self, select: fn(&mut Map)) {
+        select(&mut self.map);
+        self.emit(GameEvent::MapChanged { index: self.map.get_current_index() });
     }
 
     fn print_score(&mut self) {
@@ -214,17 +901,467 @@ impl<'a> GameEngine<'a> {
 
         let screen = if score == 0 {
             UI::print_selection_arrows()
-        } else if score as usize == self.map.get_max_score() {
+        } else if self.state.is_won {
             UI::print_trophy()
         } else {
-            UI::print_score(score)
+            UI::print_score(score, self.state.time_in_mode())
         };
 
         self.view.update(screen);
     }
 
-    fn set_speed(&mut self, speed: GameSpeed) {
-        self.state.game_speed = speed;
-        self.view.update(UI::print_speed(speed))
+    // Events are dropped if nobody picks them up for a while.
+    fn emit(&mut self, event: GameEvent) {
+        self.events.push_back(event).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::common::SNAKE_MOVE_INTERVAL_NORMAL;
+    use crate::internal_representation::button::ButtonEvent;
+    use crate::internal_representation::hazard::Hazard;
+    use crate::libs::time_util::{set_now, advance};
+    use crate::traits::GameObserver;
+
+    // A view which only remembers the last screen.
+    #[derive(Default)]
+    struct FakeView {
+        screen: Frame,
+    }
+
+    impl View for FakeView {
+        fn update(&mut self, screen: Frame) -> () {
+            self.screen = screen;
+        }
+
+        fn set_brightness(&mut self, _level: u8) -> () {}
+    }
+
+    impl Runnable for FakeView {
+        fn run_once(&mut self) -> () {}
+    }
+
+    fn click() -> ControllerInput {
+        ControllerInput::new(Some(ButtonEvent::ShortClick), Direction::NoDirection)
+    }
+
+    fn mode_changes(engine: &mut GameEngine) -> Vec<(OperationMode, OperationMode)> {
+        let mut changes = Vec::new();
+        while let Some(event) = engine.poll_event() {
+            if let GameEvent::ModeChanged { from, to } = event {
+                changes.push((from, to));
+            }
+        }
+        changes
+    }
+
+    // Runs the engine until it gets from the boot to the menu.
+    fn boot(engine: &mut GameEngine) {
+        engine.run_once();
+        assert!(mode_changes(engine) == [(OperationMode::Boot, OperationMode::Menu)]);
+    }
+
+    #[test]
+    fn goes_idle_when_left_alone_in_the_menu() {
+        set_now(Instant::from_millis(10_000));
+        let mut view = FakeView::default();
+        let statistics = RefCell::new(Statistics::default());
+        let mut engine = GameEngine::new(&mut view, Settings::default(), &statistics, EntropyPool::new());
+        boot(&mut engine);
+
+        advance(IDLE_TIMEOUT - Duration::from_secs(1));
+        engine.on_input(ControllerInput::new(None, Direction::Up));
+        advance(Duration::from_secs(1));
+        assert!(!engine.is_idle());
+
+        advance(IDLE_TIMEOUT);
+        assert!(engine.is_idle());
+    }
+
+    #[test]
+    fn buttons_are_ignored_for_a_while_after_waking_up() {
+        set_now(Instant::from_millis(10_000));
+        let mut view = FakeView::default();
+        let statistics = RefCell::new(Statistics::default());
+        let mut engine = GameEngine::new(&mut view, Settings::default(), &statistics, EntropyPool::new());
+        boot(&mut engine);
+
+        advance(IDLE_TIMEOUT);
+        engine.go_to_sleep();
+        engine.wake_up();
+        assert!(!engine.is_idle());
+
+        // The press which woke the console up doesn't start a game.
+        advance(WAKE_UP_GUARD - Duration::from_millis(10));
+        engine.on_input(click());
+        engine.run_once();
+        assert!(mode_changes(&mut engine).is_empty());
+
+        advance(Duration::from_millis(10));
+        engine.on_input(click());
+        engine.run_once();
+        assert!(mode_changes(&mut engine) == [(OperationMode::Menu, OperationMode::Countdown)]);
+    }
+
+    // An observer which remembers the events in the order they arrived.
+    #[derive(Default)]
+    struct RecordingObserver {
+        events: Vec<GameEvent>,
+    }
+
+    impl GameObserver for RecordingObserver {
+        fn on_event(&mut self, event: GameEvent) -> () {
+            self.events.push(event);
+        }
+    }
+
+    impl Runnable for RecordingObserver {
+        fn run_once(&mut self) -> () {}
+    }
+
+    // Runs the engine once and hands the events emitted meanwhile over to an
+    // observer, the same way the main loop does.
+    fn run_and_record(engine: &mut GameEngine) -> Vec<GameEvent> {
+        engine.run_once();
+        let mut observer = RecordingObserver::default();
+        while let Some(event) = engine.poll_event() {
+            observer.on_event(event);
+        }
+        observer.events
+    }
+
+    // Starts a classic game from the menu and runs the countdown.
+    fn start_game(engine: &mut GameEngine) {
+        boot(engine);
+        engine.on_input(click());
+        assert!(run_and_record(engine) == [
+            GameEvent::ModeChanged { from: OperationMode::Menu, to: OperationMode::Countdown },
+            GameEvent::GameStarted { map_index: 0, speed: GameSpeed::Normal }]);
+
+        advance(COUNTDOWN_STEP * COUNTDOWN_FROM as u32);
+        assert!(run_and_record(engine) == [
+            GameEvent::ModeChanged { from: OperationMode::Countdown, to: OperationMode::Playing }]);
+    }
+
+    // Starts a time attack from the menu and runs the countdown.
+    fn start_time_attack(engine: &mut GameEngine) {
+        boot(engine);
+        // The stick has to be released after the boot before it counts.
+        engine.on_input(ControllerInput::default());
+        engine.on_input(ControllerInput::new(None, Direction::Down));
+        assert!(run_and_record(engine) == [
+            GameEvent::ModeChanged { from: OperationMode::Menu, to: OperationMode::Countdown },
+            GameEvent::GameStarted { map_index: 0, speed: GameSpeed::Normal }]);
+
+        advance(COUNTDOWN_STEP * COUNTDOWN_FROM as u32);
+        assert!(run_and_record(engine) == [
+            GameEvent::ModeChanged { from: OperationMode::Countdown, to: OperationMode::Playing }]);
+        assert!(engine.state.game_mode == GameMode::TimeAttack);
+    }
+
+    #[test]
+    fn eating_an_apple_is_reported() {
+        set_now(Instant::from_millis(10_000));
+        let mut view = FakeView::default();
+        let statistics = RefCell::new(Statistics::default());
+        let mut engine = GameEngine::new(&mut view, Settings::default(), &statistics, EntropyPool::new());
+        start_game(&mut engine);
+
+        let ahead = engine.snake.look_ahead();
+        engine.board.add_apple(ahead);
+        advance(SNAKE_MOVE_INTERVAL_NORMAL);
+        assert!(run_and_record(&mut engine) == [GameEvent::AppleEaten]);
+    }
+
+    #[test]
+    fn death_is_reported_before_the_game_over() {
+        set_now(Instant::from_millis(10_000));
+        let mut view = FakeView::default();
+        let statistics = RefCell::new(Statistics::default());
+        let mut engine = GameEngine::new(&mut view, Settings::default(), &statistics, EntropyPool::new());
+        start_game(&mut engine);
+
+        // The snake keeps going right until it hits the wall, the grace move
+        // only delays the death.
+        let mut events = Vec::new();
+        for _ in 0..2 * BOARD_SIZE {
+            advance(SNAKE_MOVE_INTERVAL_NORMAL);
+            events = run_and_record(&mut engine);
+            if engine.state.mode != OperationMode::Playing {
+                break;
+            }
+        }
+        // The whole burst of a single run fits in the queue.
+        assert!(events == [
+            GameEvent::Died { cause: DeathCause::Wall },
+            GameEvent::ModeChanged { from: OperationMode::Playing, to: OperationMode::GameOver },
+            GameEvent::GameEnded { longest_snake: engine.state.longest_snake }]);
+
+        advance(GAME_OVER_DURATION);
+        assert!(run_and_record(&mut engine) == [
+            GameEvent::ModeChanged { from: OperationMode::GameOver, to: OperationMode::Menu }]);
+    }
+
+    #[test]
+    fn a_death_during_a_time_attack_costs_time() {
+        set_now(Instant::from_millis(10_000));
+        let mut view = FakeView::default();
+        let statistics = RefCell::new(Statistics::default());
+        let mut engine = GameEngine::new(&mut view, Settings::default(), &statistics, EntropyPool::new());
+        start_time_attack(&mut engine);
+        let ends_at = engine.state.time_attack_ends_at;
+
+        // The snake keeps going right until it hits the wall.
+        let mut events = Vec::new();
+        for _ in 0..2 * BOARD_SIZE {
+            advance(SNAKE_MOVE_INTERVAL_NORMAL);
+            events = run_and_record(&mut engine);
+            if events.contains(&GameEvent::Died { cause: DeathCause::Wall }) {
+                break;
+            }
+        }
+        assert!(events == [GameEvent::Died { cause: DeathCause::Wall }]);
+        assert!(engine.state.mode == OperationMode::Playing);
+        assert!(engine.state.time_attack_ends_at == ends_at - TIME_ATTACK_DEATH_PENALTY);
+        assert!(engine.snake.segments().eq(Snake::new(engine.rules.starting_length).segments()));
+    }
+
+    #[test]
+    fn respawning_clears_the_snake_and_what_was_under_its_start() {
+        set_now(Instant::from_millis(10_000));
+        let mut view = FakeView::default();
+        let statistics = RefCell::new(Statistics::default());
+        let mut engine = GameEngine::new(&mut view, Settings::default(), &statistics, EntropyPool::new());
+        start_time_attack(&mut engine);
+
+        // The snake went away from its start, which now holds an apple and an
+        // item.
+        engine.rules.starting_length = 2;
+        engine.board = GameBoard::new(Frame::default());
+        engine.snake = Snake::new(1);
+        engine.snake.move_to(Point::new(1, 0));
+        engine.snake.advance_tail();
+        engine.snake.move_to(Point::new(2, 0));
+        for segment in engine.snake.segments() {
+            engine.board.add_snake_segment(segment);
+        }
+        engine.board.add_apple(Point::new(0, 0));
+        engine.board.update_board_entry(Point::new(0, 1), BoardCell::Item(Item::ShrinkPill));
+        engine.placed_item = Some((Point::new(0, 1), now() + Duration::from_secs(5)));
+
+        engine.respawn();
+        assert!(engine.board.read_board_at(Point::new(1, 0)) == BoardCell::Empty);
+        assert!(engine.board.read_board_at(Point::new(2, 0)) == BoardCell::Empty);
+        assert!(engine.board.read_board_at(Point::new(0, 0)) == BoardCell::Snake);
+        assert!(engine.board.read_board_at(Point::new(0, 1)) == BoardCell::Snake);
+        assert!(engine.snake.len() == 2);
+        assert!(engine.placed_item.is_none());
+
+        // The apple went somewhere else.
+        let mut apples = 0;
+        for y in 0..BOARD_SIZE as i8 {
+            for x in 0..BOARD_SIZE as i8 {
+                if engine.board.read_board_at(Point::new(x, y)) == BoardCell::Apple {
+                    apples += 1;
+                }
+            }
+        }
+        assert!(apples == 1);
+    }
+
+    #[test]
+    fn the_time_attack_ends_when_the_time_is_up() {
+        set_now(Instant::from_millis(10_000));
+        let mut view = FakeView::default();
+        let statistics = RefCell::new(Statistics::default());
+        let mut engine = GameEngine::new(&mut view, Settings::default(), &statistics, EntropyPool::new());
+        start_time_attack(&mut engine);
+        engine.state.score = 7;
+
+        set_now(engine.state.time_attack_ends_at);
+        assert!(run_and_record(&mut engine) == [
+            GameEvent::TimeUp,
+            GameEvent::ModeChanged { from: OperationMode::Playing, to: OperationMode::GameOver },
+            GameEvent::GameEnded { longest_snake: 1 }]);
+
+        let best_scores = engine.state.best_scores();
+        assert!(best_scores[GameMode::TimeAttack as usize] == 7);
+        assert!(best_scores[GameMode::Classic as usize] == 0);
+    }
+
+    #[test]
+    fn quitting_from_the_pause_ends_the_game() {
+        set_now(Instant::from_millis(10_000));
+        let mut view = FakeView::default();
+        let statistics = RefCell::new(Statistics::default());
+        let mut engine = GameEngine::new(&mut view, Settings::default(), &statistics, EntropyPool::new());
+        start_game(&mut engine);
+
+        engine.on_input(click());
+        assert!(run_and_record(&mut engine) == [
+            GameEvent::ModeChanged { from: OperationMode::Playing, to: OperationMode::Paused }]);
+
+        engine.on_input(ControllerInput::new(Some(ButtonEvent::LongPress), Direction::NoDirection));
+        assert!(run_and_record(&mut engine) == [
+            GameEvent::ModeChanged { from: OperationMode::Paused, to: OperationMode::GameOver },
+            GameEvent::GameEnded { longest_snake: 1 }]);
+    }
+
+    #[test]
+    fn events_beyond_the_queue_capacity_are_dropped() {
+        set_now(Instant::from_millis(10_000));
+        let mut view = FakeView::default();
+        let statistics = RefCell::new(Statistics::default());
+        let mut engine = GameEngine::new(&mut view, Settings::default(), &statistics, EntropyPool::new());
+        while engine.poll_event().is_some() {}
+
+        for index in 0..MAX_PENDING_EVENTS + 2 {
+            engine.emit(GameEvent::MapChanged { index });
+        }
+
+        let mut observer = RecordingObserver::default();
+        while let Some(event) = engine.poll_event() {
+            observer.on_event(event);
+        }
+        let kept: Vec<GameEvent> = (0..MAX_PENDING_EVENTS)
+            .map(|index| GameEvent::MapChanged { index })
+            .collect();
+        assert!(observer.events == kept);
+    }
+
+    #[test]
+    fn only_a_running_slow_motion_is_postponed() {
+        set_now(Instant::from_millis(10_000));
+        let mut view = FakeView::default();
+        let statistics = RefCell::new(Statistics::default());
+        let mut engine = GameEngine::new(&mut view, Settings::default(), &statistics, EntropyPool::new());
+        let pause = Duration::from_secs(2);
+
+        // It ran out while paused, the rest of it is still due after the pause.
+        engine.state.slow_motion_until = now() - Duration::from_secs(1);
+        engine.postpone_timers(pause);
+        assert!(engine.state.slow_motion_until == now() + Duration::from_secs(1));
+
+        // It was over before the pause.
+        engine.state.slow_motion_until = now() - Duration::from_secs(3);
+        engine.postpone_timers(pause);
+        assert!(engine.state.slow_motion_until == now() - Duration::from_secs(3));
+    }
+
+    // A patrol which runs into the right wall of the seven by seven map.
+    const WALL_PATROL: [Hazard; 1] = [
+        Hazard::Patrol { row: 3, from: 5, to: 7, step: Duration::from_millis(100) },
+    ];
+
+    // Clears the board of a game in progress down to the layout of a map and
+    // a snake of a single segment.
+    fn clear_board(engine: &mut GameEngine, map_index: usize, head: Point) {
+        engine.map.select(map_index);
+        engine.board = GameBoard::new(engine.map.get_current_map());
+        engine.snake = Snake::new(1);
+        engine.snake.move_to(head);
+        engine.snake.advance_tail();
+        engine.board.add_snake_segment(head);
+    }
+
+    #[test]
+    fn hazards_leave_the_layout_of_the_map_behind() {
+        set_now(Instant::from_millis(10_000));
+        let mut view = FakeView::default();
+        let statistics = RefCell::new(Statistics::default());
+        let mut engine = GameEngine::new(&mut view, Settings::default(), &statistics, EntropyPool::new());
+        start_game(&mut engine);
+        clear_board(&mut engine, 1, Point::new(1, 0));
+        engine.hazards = Hazards::new(&WALL_PATROL);
+
+        let row = |engine: &GameEngine| -> Vec<bool> {
+            (5..BOARD_SIZE as i8)
+                .map(|x| engine.board.read_board_at(Point::new(x, 3)) == BoardCell::Obstacle)
+                .collect()
+        };
+        engine.update_hazards();
+        assert!(row(&engine) == [true, false, true]);
+        advance(Duration::from_millis(100));
+        engine.update_hazards();
+        assert!(row(&engine) == [false, true, true]);
+        advance(Duration::from_millis(100));
+        engine.update_hazards();
+        assert!(row(&engine) == [false, false, true]);
+
+        // The wall of the map stays where the patrol turned around.
+        advance(Duration::from_millis(100));
+        engine.update_hazards();
+        assert!(row(&engine) == [false, true, true]);
+        assert!(engine.state.mode == OperationMode::Playing);
+    }
+
+    #[test]
+    fn a_hazard_running_into_the_snake_kills_it() {
+        set_now(Instant::from_millis(10_000));
+        let mut view = FakeView::default();
+        let statistics = RefCell::new(Statistics::default());
+        let mut engine = GameEngine::new(&mut view, Settings::default(), &statistics, EntropyPool::new());
+        start_game(&mut engine);
+        clear_board(&mut engine, 0, Point::new(6, 3));
+        engine.hazards = Hazards::new(&WALL_PATROL);
+
+        engine.update_hazards();
+        assert!(engine.poll_event().is_none());
+
+        advance(Duration::from_millis(100));
+        engine.update_hazards();
+        assert!(engine.poll_event() == Some(GameEvent::Died { cause: DeathCause::Obstacle }));
+        assert!(engine.state.mode == OperationMode::GameOver);
+    }
+
+    #[test]
+    fn apples_stay_out_of_the_reach_of_the_hazards() {
+        const WALL: [Hazard; 1] = [
+            Hazard::ShiftingWall {
+                columns: 0b_01111111, from_row: 1, to_row: 7, apples_per_shift: 1 },
+        ];
+        set_now(Instant::from_millis(10_000));
+        let mut view = FakeView::default();
+        let statistics = RefCell::new(Statistics::default());
+        let mut engine = GameEngine::new(&mut view, Settings::default(), &statistics, EntropyPool::new());
+        start_game(&mut engine);
+        clear_board(&mut engine, 0, Point::new(0, 0));
+        engine.hazards = Hazards::new(&WALL);
+
+        let reach = engine.hazards.reach();
+        for _ in 0..32 {
+            let placement = engine.spawn_apple();
+            assert!(placement != ApplePlacement::BoardFull);
+            if let ApplePlacement::At(point) = placement {
+                assert!(!reach.is_lit(point.x as usize, point.y as usize));
+                engine.board.erase_entry(point);
+            }
+        }
+    }
+
+    #[test]
+    fn apple_replaces_an_item_on_the_last_free_cell() {
+        set_now(Instant::from_millis(10_000));
+        let mut view = FakeView::default();
+        let statistics = RefCell::new(Statistics::default());
+        let mut engine = GameEngine::new(&mut view, Settings::default(), &statistics, EntropyPool::new());
+
+        let last_cell = Point::new(3, 4);
+        for y in 0..BOARD_SIZE as i8 {
+            for x in 0..BOARD_SIZE as i8 {
+                engine.board.update_board_entry(Point::new(x, y), BoardCell::Snake);
+            }
+        }
+        engine.board.update_board_entry(last_cell, BoardCell::Item(Item::ShrinkPill));
+        engine.placed_item = Some((last_cell, now() + Duration::from_secs(5)));
+
+        assert!(engine.spawn_apple() == ApplePlacement::At(last_cell));
+        assert!(engine.board.read_board_at(last_cell) == BoardCell::Apple);
+        assert!(engine.placed_item.is_none());
+        assert!(engine.spawn_apple() == ApplePlacement::BoardFull);
     }
 }
+
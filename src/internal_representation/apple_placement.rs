@@ -0,0 +1,316 @@
+// Picks the cell for the next apple uniformly among the free cells of the
+// board, which takes the same time regardless of how full the board is.
+
+use oorandom::Rand32;
+
+use crate::common::BOARD_SIZE;
+
+use super::frame::Frame;
+use super::game_board::{GameBoard, BoardCell};
+use super::point::Point;
+use super::rules::{Rules, WallMode};
+
+// Optional restrictions on the cells the apple may appear in. They are only
+// preferences, if no free cell satisfies them the apple goes to any free cell.
+#[derive(Copy, Clone, PartialEq)]
+pub struct PlacementPolicy {
+    // Keeps the apple out of the cells right next to the head of the snake.
+    pub avoid_head: bool,
+    // Keeps the apple out of the cells which can only be entered from one side.
+    pub avoid_dead_ends: bool,
+}
+
+impl PlacementPolicy {
+    pub const ANYWHERE: PlacementPolicy = PlacementPolicy {
+        avoid_head: false,
+        avoid_dead_ends: false,
+    };
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum ApplePlacement {
+    At(Point),
+    BoardFull,
+}
+
+const CELLS_NUMBER: u16 = (BOARD_SIZE * BOARD_SIZE) as u16;
+
+/*
+ The cells of the board in an order which only depends on the seed, so that
+ the apples of a challenge show up in the same places whatever the snake did
+ in between. The order is a full period linear congruential sequence: with
+ the multiplier one more than a multiple of four and an odd increment, it
+ visits every cell once before it repeats, without keeping a list of them.
+
+ An apple goes to the next cell of the sequence which is free, the occupied
+ ones are skipped. The placement policy doesn't apply.
+*/
+#[derive(Copy, Clone, PartialEq)]
+pub struct AppleSequence {
+    cell: u16,
+    multiplier: u16,
+    increment: u16,
+}
+
+impl AppleSequence {
+    pub fn new(seed: u64) -> AppleSequence {
+        AppleSequence {
+            cell: (seed % CELLS_NUMBER as u64) as u16,
+            multiplier: ((seed >> 16) % (CELLS_NUMBER / 4) as u64) as u16 * 4 + 1,
+            increment: ((seed >> 32) % (CELLS_NUMBER / 2) as u64) as u16 * 2 + 1,
+        }
+    }
+
+    pub fn place_apple(&mut self, board: &GameBoard, avoided: Frame) -> ApplePlacement {
+        for _ in 0..CELLS_NUMBER {
+            self.cell = (self.cell * self.multiplier + self.increment) % CELLS_NUMBER;
+            let point = Point::new(
+                (self.cell % BOARD_SIZE as u16) as i8,
+                (self.cell / BOARD_SIZE as u16) as i8);
+            if is_free(board, avoided, point) {
+                return ApplePlacement::At(point);
+            }
+        }
+        ApplePlacement::BoardFull
+    }
+}
+
+// The apple is kept out of the cells listed as avoided, e.g. the ones moving
+// hazards may run over.
+pub fn place_apple(
+    board: &GameBoard,
+    head: Point,
+    avoided: Frame,
+    rules: &Rules,
+    generator: &mut Rand32) -> ApplePlacement {
+
+    let policy = rules.apple_placement;
+    let preferred = |point: Point| {
+        !(policy.avoid_head && is_adjacent(point, head)) &&
+        !(policy.avoid_dead_ends && is_dead_end(board, point, rules.wall_mode))
+    };
+
+    match pick_free_cell(board, avoided, generator, preferred) {
+        Some(point) => ApplePlacement::At(point),
+        None        => match pick_free_cell(board, avoided, generator, |_| true) {
+            Some(point) => ApplePlacement::At(point),
+            None        => ApplePlacement::BoardFull,
+        },
+    }
+}
+
+// The free cells are counted first and then enumerated again up to the drawn
+// one, so that no list of them needs to be kept in memory.
+fn pick_free_cell<F>(board: &GameBoard, avoided: Frame, generator: &mut Rand32, filter: F)
+    -> Option<Point> where F: Fn(Point) -> bool {
+
+    let count = free_cells(board, avoided).filter(|point| filter(*point)).count();
+    if count == 0 {
+        return None;
+    }
+
+    let index = generator.rand_range(0..count as u32) as usize;
+    free_cells(board, avoided).filter(|point| filter(*point)).nth(index)
+}
+
+fn free_cells(board: &GameBoard, avoided: Frame) -> impl Iterator<Item = Point> + '_ {
+    (0..BOARD_SIZE as i8)
+        .flat_map(|y| (0..BOARD_SIZE as i8).map(move |x| Point::new(x, y)))
+        .filter(move |point| is_free(board, avoided, *point))
+}
+
+fn is_free(board: &GameBoard, avoided: Frame, point: Point) -> bool {
+    board.is_within_bounds(point) &&
+    !avoided.is_lit(point.x as usize, point.y as usize) &&
+    board.read_board_at(point) == BoardCell::Empty
+}
+
+fn neighbours(point: Point) -> [Point; 4] {
+    [point.translate_up(), point.translate_down(),
+     point.translate_left(), point.translate_right()]
+}
+
+fn is_adjacent(point: Point, other: Point) -> bool {
+    neighbours(other).contains(&point)
+}
+
+// A cell with at most one open side traps the snake once it goes in for the
+// apple. The walls only close a side if the snake can't go through them.
+fn is_dead_end(board: &GameBoard, point: Point, wall_mode: WallMode) -> bool {
+    let open_sides = neighbours(point).iter()
+        .filter(|neighbour| {
+            let neighbour = match (board.is_within_bounds(**neighbour), wall_mode) {
+                (true, _)                => **neighbour,
+                (false, WallMode::Wrap)  => board.wrap(**neighbour),
+                (false, WallMode::Solid) => return false,
+            };
+            board.read_board_at(neighbour) != BoardCell::Obstacle
+        })
+        .count();
+    open_sides <= 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(x: i8, y: i8) -> Point {
+        Point::new(x, y)
+    }
+
+    // A board covered with obstacles apart from the given cells.
+    fn board_with_free_cells(free_cells: &[Point]) -> GameBoard {
+        let mut obstacles = Frame::default();
+        for y in 0..BOARD_SIZE {
+            for x in 0..BOARD_SIZE {
+                if !free_cells.contains(&point(x as i8, y as i8)) {
+                    obstacles.light_up(x, y);
+                }
+            }
+        }
+        GameBoard::new(obstacles)
+    }
+
+    fn rules(policy: PlacementPolicy, wall_mode: WallMode) -> Rules {
+        Rules { apple_placement: policy, wall_mode, ..Rules::CLASSIC }
+    }
+
+    // The cells the apple goes to over many draws.
+    fn placements(board: &GameBoard, head: Point, rules: &Rules) -> Vec<Point> {
+        let mut cells = Vec::new();
+        for seed in 0..64 {
+            match place_apple(board, head, Frame::default(), rules, &mut Rand32::new(seed)) {
+                ApplePlacement::At(cell) if !cells.contains(&cell) => cells.push(cell),
+                ApplePlacement::At(_)                              => (),
+                ApplePlacement::BoardFull                          => assert!(false),
+            }
+        }
+        cells.sort_by_key(|cell| (cell.y, cell.x));
+        cells
+    }
+
+    const AVOID_HEAD: PlacementPolicy =
+        PlacementPolicy { avoid_head: true, avoid_dead_ends: false };
+    const AVOID_DEAD_ENDS: PlacementPolicy =
+        PlacementPolicy { avoid_head: false, avoid_dead_ends: true };
+
+    #[test]
+    fn full_board_has_no_room_for_an_apple() {
+        let board = board_with_free_cells(&[]);
+        let placement = place_apple(
+            &board, point(0, 0), Frame::default(), &Rules::CLASSIC, &mut Rand32::new(1));
+        assert!(placement == ApplePlacement::BoardFull);
+    }
+
+    #[test]
+    fn apple_goes_to_the_only_free_cell() {
+        let board = board_with_free_cells(&[point(5, 2)]);
+        assert!(placements(&board, point(0, 0), &Rules::CLASSIC) == [point(5, 2)]);
+    }
+
+    #[test]
+    fn apples_go_to_every_free_cell() {
+        let board = board_with_free_cells(&[point(1, 1), point(6, 3), point(2, 7)]);
+        assert!(placements(&board, point(0, 0), &Rules::CLASSIC) ==
+            [point(1, 1), point(6, 3), point(2, 7)]);
+    }
+
+    #[test]
+    fn apples_can_keep_away_from_the_head() {
+        let board = board_with_free_cells(&[point(3, 3), point(4, 4), point(6, 6)]);
+        let head = point(3, 4);
+        assert!(placements(&board, head, &rules(AVOID_HEAD, WallMode::Solid)) == [point(6, 6)]);
+        assert!(placements(&board, head, &Rules::CLASSIC).len() == 3);
+    }
+
+    #[test]
+    fn solid_walls_close_the_sides_of_a_dead_end() {
+        let mut board = GameBoard::default();
+        board.update_board_entry(point(1, 0), BoardCell::Obstacle);
+        board.update_board_entry(point(0, 1), BoardCell::Obstacle);
+
+        assert!(is_dead_end(&board, point(0, 0), WallMode::Solid));
+        assert!(!is_dead_end(&board, point(0, 0), WallMode::Wrap));
+        assert!(!is_dead_end(&board, point(7, 7), WallMode::Solid));
+    }
+
+    #[test]
+    fn apples_can_keep_out_of_dead_ends() {
+        // A free row and a lone free cell in the corner.
+        let mut free_cells: Vec<Point> = (0..BOARD_SIZE as i8).map(|x| point(x, 4)).collect();
+        free_cells.push(point(0, 0));
+        let board = board_with_free_cells(&free_cells);
+        let head = point(3, 4);
+
+        // The ends of the row are dead ends unless the snake can go through
+        // the walls, the corner is one either way.
+        let inner_cells: Vec<Point> = (1..BOARD_SIZE as i8 - 1).map(|x| point(x, 4)).collect();
+        assert!(placements(&board, head, &rules(AVOID_DEAD_ENDS, WallMode::Solid)) == inner_cells);
+        assert!(placements(&board, head, &rules(AVOID_DEAD_ENDS, WallMode::Wrap)) ==
+            free_cells[..BOARD_SIZE].to_vec());
+    }
+
+    #[test]
+    fn apples_go_anywhere_free_if_no_cell_is_preferred() {
+        let board = board_with_free_cells(&[point(3, 3)]);
+        let policy = PlacementPolicy { avoid_head: true, avoid_dead_ends: true };
+        assert!(placements(&board, point(3, 4), &rules(policy, WallMode::Solid)) == [point(3, 3)]);
+    }
+
+    // Places apples until the board is full, each apple stays where it went.
+    fn apples(sequence: &mut AppleSequence, board: &mut GameBoard) -> Vec<Point> {
+        let mut apples = Vec::new();
+        while let ApplePlacement::At(point) = sequence.place_apple(board, Frame::default()) {
+            board.add_apple(point);
+            apples.push(point);
+        }
+        apples
+    }
+
+    #[test]
+    fn sequence_visits_every_cell_once() {
+        for &seed in [0, 1, 0x1234_5678_9abc, u64::MAX].iter() {
+            let mut cells = apples(&mut AppleSequence::new(seed), &mut GameBoard::default());
+            assert!(cells.len() == CELLS_NUMBER as usize);
+            cells.sort_by_key(|point| (point.y, point.x));
+            cells.dedup();
+            assert!(cells.len() == CELLS_NUMBER as usize);
+        }
+    }
+
+    #[test]
+    fn occupied_cells_dont_change_the_order_of_the_others() {
+        let seed = 0xfeed_beef_cafe;
+        let everywhere = apples(&mut AppleSequence::new(seed), &mut GameBoard::default());
+
+        let mut board = GameBoard::default();
+        let occupied = [Point::new(0, 0), Point::new(3, 4), Point::new(7, 7)];
+        for point in occupied.iter() {
+            board.add_snake_segment(*point);
+        }
+        let around_the_snake = apples(&mut AppleSequence::new(seed), &mut board);
+
+        let expected: Vec<Point> = everywhere.into_iter()
+            .filter(|point| !occupied.contains(point))
+            .collect();
+        assert!(around_the_snake == expected);
+    }
+
+    #[test]
+    fn avoided_cells_are_skipped() {
+        let mut avoided = Frame::default();
+        for x in 0..BOARD_SIZE {
+            for y in 1..BOARD_SIZE {
+                avoided.light_up(x, y);
+            }
+        }
+
+        let mut sequence = AppleSequence::new(42);
+        for _ in 0..BOARD_SIZE {
+            match sequence.place_apple(&GameBoard::default(), avoided) {
+                ApplePlacement::At(point) => assert!(point.y == 0),
+                ApplePlacement::BoardFull => assert!(false),
+            }
+        }
+    }
+}
@@ -0,0 +1,249 @@
+// Recognition of button gestures from raw readings of a push button pin.
+
+use crate::libs::ring_buffer::RingBuffer;
+use crate::libs::time::{Instant, Duration};
+
+// The time for which the pin reading needs to stay the same for the change to
+// be accepted, it filters out the bouncing of the switch contacts.
+const DEBOUNCE_INTERVAL: Duration = Duration::from_millis(20);
+
+// The time the button needs to be held down for to register a long press.
+const LONG_PRESS_DURATION: Duration = Duration::from_millis(600);
+
+// The maximum time between releasing the button and pressing it again for the
+// two clicks to be registered as a double click.
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(250);
+
+const MAX_PENDING_EVENTS: usize = 4;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum ButtonEvent {
+    Press,
+    Release,
+    ShortClick,
+    LongPress,
+    DoubleClick,
+}
+
+/*
+ The debouncer is fed with the raw state of the button pin together with the
+ time of the reading. A press or release is only accepted once the reading has
+ been stable for DEBOUNCE_INTERVAL. Gestures are derived from the accepted
+ presses and releases:
+  - a press held for LONG_PRESS_DURATION is a long press, its release doesn't
+    produce any click,
+  - a press followed by a release is a click, it is reported as a short click
+    only after DOUBLE_CLICK_INTERVAL passes without another press,
+  - a second click within DOUBLE_CLICK_INTERVAL is reported as a double click.
+ A single update can produce several events (e.g. a release and a double click)
+ hence they are queued and handed out one per update.
+*/
+pub struct ButtonDebouncer {
+    raw_pressed: bool,
+    raw_changed_at: Instant,
+    is_pressed: bool,
+    pressed_at: Instant,
+    is_long_press_reported: bool,
+    click_released_at: Option<Instant>,
+    is_second_click: bool,
+    events: RingBuffer<ButtonEvent, MAX_PENDING_EVENTS>,
+}
+
+impl ButtonDebouncer {
+    pub fn new() -> ButtonDebouncer {
+        ButtonDebouncer {
+            raw_pressed: false,
+            raw_changed_at: Instant::default(),
+            is_pressed: false,
+            pressed_at: Instant::default(),
+            is_long_press_reported: false,
+            click_released_at: None,
+            is_second_click: false,
+            events: RingBuffer::new(),
+        }
+    }
+
+    pub fn update(&mut self, raw_pressed: bool, now: Instant) -> Option<ButtonEvent> {
+        if raw_pressed != self.raw_pressed {
+            self.raw_pressed = raw_pressed;
+            self.raw_changed_at = now;
+        }
+
+        // The second press only gets accepted after debouncing, hence its raw
+        // reading decides whether it came in time for a double click.
+        if let Some(released_at) = self.click_released_at {
+            let is_second_press_in_time = self.raw_pressed &&
+                self.raw_changed_at - released_at <= DOUBLE_CLICK_INTERVAL;
+
+            if !is_second_press_in_time &&
+               now - released_at > DOUBLE_CLICK_INTERVAL {
+                self.click_released_at = None;
+                self.emit(ButtonEvent::ShortClick);
+            }
+        }
+
+        let is_stable = now - self.raw_changed_at >= DEBOUNCE_INTERVAL;
+        if is_stable && self.raw_pressed != self.is_pressed {
+            self.is_pressed = self.raw_pressed;
+            if self.is_pressed { self.on_press(now) } else { self.on_release(now) }
+        }
+
+        if self.is_pressed && !self.is_long_press_reported &&
+           now - self.pressed_at >= LONG_PRESS_DURATION {
+            self.is_long_press_reported = true;
+            self.is_second_click = false;
+            self.emit(ButtonEvent::LongPress);
+        }
+
+        self.events.pop_front()
+    }
+
+    pub fn is_pressed(&self) -> bool {
+        self.is_pressed
+    }
+
+    fn on_press(&mut self, now: Instant) {
+        self.pressed_at = now;
+        self.is_long_press_reported = false;
+        self.is_second_click = self.click_released_at.take().is_some();
+        self.emit(ButtonEvent::Press);
+    }
+
+    fn on_release(&mut self, now: Instant) {
+        self.emit(ButtonEvent::Release);
+
+        if self.is_long_press_reported {
+            return;
+        }
+
+        if self.is_second_click {
+            self.is_second_click = false;
+            self.emit(ButtonEvent::DoubleClick);
+        } else {
+            self.click_released_at = Some(now);
+        }
+    }
+
+    // Events are dropped if nobody picks them up for a while.
+    fn emit(&mut self, event: ButtonEvent) {
+        self.events.push_back(event).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Feeds the same reading once every millisecond from `from` up to `to`.
+    fn hold(debouncer: &mut ButtonDebouncer, pressed: bool, from: u32, to: u32) -> Vec<ButtonEvent> {
+        (from..to)
+            .filter_map(|millis| debouncer.update(pressed, Instant::from_millis(millis)))
+            .collect()
+    }
+
+    #[test]
+    fn bouncing_shorter_than_debounce_interval_is_ignored() {
+        let mut debouncer = ButtonDebouncer::new();
+        assert!(hold(&mut debouncer, false, 0, 100).is_empty());
+
+        for millis in (100..140).step_by(5) {
+            let pressed = millis % 10 == 0;
+            assert!(hold(&mut debouncer, pressed, millis, millis + 5).is_empty());
+        }
+        assert!(hold(&mut debouncer, false, 140, 400).is_empty());
+        assert!(!debouncer.is_pressed());
+    }
+
+    #[test]
+    fn press_is_accepted_once_reading_is_stable() {
+        let mut debouncer = ButtonDebouncer::new();
+        hold(&mut debouncer, false, 0, 100);
+        hold(&mut debouncer, true, 100, 103);
+        hold(&mut debouncer, false, 103, 105);
+
+        assert!(hold(&mut debouncer, true, 105, 125).is_empty());
+        assert!(hold(&mut debouncer, true, 125, 126) == [ButtonEvent::Press]);
+        assert!(debouncer.is_pressed());
+    }
+
+    #[test]
+    fn click_is_reported_as_short_click_after_double_click_interval() {
+        let mut debouncer = ButtonDebouncer::new();
+        hold(&mut debouncer, false, 0, 100);
+
+        assert!(hold(&mut debouncer, true, 100, 200) == [ButtonEvent::Press]);
+        assert!(hold(&mut debouncer, false, 200, 471) == [ButtonEvent::Release]);
+        assert!(hold(&mut debouncer, false, 471, 472) == [ButtonEvent::ShortClick]);
+        assert!(hold(&mut debouncer, false, 472, 1000).is_empty());
+    }
+
+    #[test]
+    fn press_held_for_long_press_duration_is_long_press() {
+        let mut debouncer = ButtonDebouncer::new();
+        hold(&mut debouncer, false, 0, 100);
+
+        // The press is accepted at 120.
+        assert!(hold(&mut debouncer, true, 100, 720) == [ButtonEvent::Press]);
+        assert!(hold(&mut debouncer, true, 720, 721) == [ButtonEvent::LongPress]);
+        assert!(hold(&mut debouncer, true, 721, 2000).is_empty());
+        assert!(hold(&mut debouncer, false, 2000, 3000) == [ButtonEvent::Release]);
+    }
+
+    #[test]
+    fn second_click_within_interval_is_double_click() {
+        let mut debouncer = ButtonDebouncer::new();
+        hold(&mut debouncer, false, 0, 100);
+
+        let mut events = hold(&mut debouncer, true, 100, 200);
+        // The release is accepted at 220, the second press comes 250 ms later.
+        events.extend(hold(&mut debouncer, false, 200, 470));
+        events.extend(hold(&mut debouncer, true, 470, 550));
+        events.extend(hold(&mut debouncer, false, 550, 1500));
+
+        assert!(events == [
+            ButtonEvent::Press,
+            ButtonEvent::Release,
+            ButtonEvent::Press,
+            ButtonEvent::Release,
+            ButtonEvent::DoubleClick,
+        ]);
+    }
+
+    #[test]
+    fn second_click_after_interval_is_another_short_click() {
+        let mut debouncer = ButtonDebouncer::new();
+        hold(&mut debouncer, false, 0, 100);
+
+        let mut events = hold(&mut debouncer, true, 100, 200);
+        events.extend(hold(&mut debouncer, false, 200, 471));
+        events.extend(hold(&mut debouncer, true, 471, 550));
+        events.extend(hold(&mut debouncer, false, 550, 1500));
+
+        assert!(events == [
+            ButtonEvent::Press,
+            ButtonEvent::Release,
+            ButtonEvent::ShortClick,
+            ButtonEvent::Press,
+            ButtonEvent::Release,
+            ButtonEvent::ShortClick,
+        ]);
+    }
+
+    #[test]
+    fn events_beyond_queue_capacity_are_dropped() {
+        let mut debouncer = ButtonDebouncer::new();
+        debouncer.emit(ButtonEvent::Press);
+        debouncer.emit(ButtonEvent::Release);
+        debouncer.emit(ButtonEvent::Press);
+        debouncer.emit(ButtonEvent::Release);
+        debouncer.emit(ButtonEvent::DoubleClick);
+
+        let events = hold(&mut debouncer, false, 0, 10);
+        assert!(events == [
+            ButtonEvent::Press,
+            ButtonEvent::Release,
+            ButtonEvent::Press,
+            ButtonEvent::Release,
+        ]);
+    }
+}
@@ -0,0 +1,187 @@
+// Calibration of the analog stick, it records the readings of the stick in its
+// neutral position and the way the stick is mounted. The dead zone follows the
+// sensitivity in the settings instead.
+
+use crate::common::CALIBRATION_ADDRESS;
+use crate::traits::Storage;
+
+use super::record::{load_record, save_record};
+
+const CALIBRATION_VERSION: u8 = 2;
+const CALIBRATION_LENGTH: usize = 5;
+
+// The readings of the analog pins range from 0 to ANALOG_MAX.
+const ANALOG_MAX: u16 = 1023;
+
+// The displacement in percent the stick needs to be pushed by to tell the
+// way it is mounted.
+const ORIENTATION_THRESHOLD: i16 = 80;
+
+const INVERT_X: u8 = 1 << 0;
+const INVERT_Y: u8 = 1 << 1;
+const SWAP_AXES: u8 = 1 << 2;
+
+#[derive(Copy, Clone)]
+pub struct Calibration {
+    pub center_x: u16,
+    pub center_y: u16,
+    pub invert_x: bool,
+    pub invert_y: bool,
+    pub swap_axes: bool, // For sticks mounted rotated by 90 degrees.
+}
+
+impl Calibration {
+    pub fn load(storage: &dyn Storage) -> Option<Calibration> {
+        let mut data = [0; CALIBRATION_LENGTH];
+        if !load_record(storage, CALIBRATION_ADDRESS, CALIBRATION_VERSION, &mut data) {
+            return None;
+        }
+
+        Some(Calibration {
+            center_x: u16::from_le_bytes([data[0], data[1]]),
+            center_y: u16::from_le_bytes([data[2], data[3]]),
+            invert_x: data[4] & INVERT_X != 0,
+            invert_y: data[4] & INVERT_Y != 0,
+            swap_axes: data[4] & SWAP_AXES != 0,
+        })
+    }
+
+    pub fn save(&self, storage: &mut dyn Storage) {
+        let x = self.center_x.to_le_bytes();
+        let y = self.center_y.to_le_bytes();
+        let mut flags = 0;
+        if self.invert_x  { flags |= INVERT_X; }
+        if self.invert_y  { flags |= INVERT_Y; }
+        if self.swap_axes { flags |= SWAP_AXES; }
+
+        let data = [x[0], x[1], y[0], y[1], flags];
+        save_record(storage, CALIBRATION_ADDRESS, CALIBRATION_VERSION, &data);
+    }
+
+    // Returns the displacement of the stick along both axes in percent of the
+    // full range in a given direction, adjusted for the way the stick is
+    // mounted. Both values range from -100 to 100.
+    pub fn displacement(&self, x_value: u16, y_value: u16) -> (i16, i16) {
+        let mut x = axis_displacement(x_value, self.center_x);
+        let mut y = axis_displacement(y_value, self.center_y);
+
+        if self.invert_x  { x = -x; }
+        if self.invert_y  { y = -y; }
+        if self.swap_axes { core::mem::swap(&mut x, &mut y); }
+
+        (x, y)
+    }
+
+    // Works out the way the stick is mounted from a reading taken while it is
+    // pushed up, a stick may be rotated but not mirrored. Returns false while
+    // the stick isn't pushed far enough to tell.
+    pub fn orient(&mut self, x_value: u16, y_value: u16) -> bool {
+        let x = axis_displacement(x_value, self.center_x);
+        let y = axis_displacement(y_value, self.center_y);
+        if x.abs().max(y.abs()) < ORIENTATION_THRESHOLD {
+            return false;
+        }
+
+        // Once oriented, up is a negative displacement along the x axis.
+        let (invert_x, invert_y, swap_axes) = match (x.abs() >= y.abs(), x < 0, y < 0) {
+            (true, true, _)   => (false, false, false), // As designed.
+            (true, false, _)  => (true, true, false),   // Upside down.
+            (false, _, true)  => (true, false, true),   // Rotated a quarter turn.
+            (false, _, false) => (false, true, true),   // Rotated three quarters.
+        };
+        self.invert_x = invert_x;
+        self.invert_y = invert_y;
+        self.swap_axes = swap_axes;
+        true
+    }
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Self {
+            center_x: ANALOG_MAX / 2,
+            center_y: ANALOG_MAX / 2,
+            invert_x: false,
+            invert_y: false,
+            swap_axes: false,
+        }
+    }
+}
+
+fn axis_displacement(value: u16, center: u16) -> i16 {
+    let value = value.min(ANALOG_MAX) as i32;
+    let center = center.clamp(1, ANALOG_MAX - 1) as i32;
+
+    let displacement = if value < center {
+        (value - center) * 100 / center
+    } else {
+        (value - center) * 100 / (ANALOG_MAX as i32 - center)
+    };
+
+    displacement as i16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal_representation::direction::Direction;
+    use crate::internal_representation::record::FakeStorage;
+    use crate::internal_representation::stick_direction::determine_direction;
+
+    const CENTER: u16 = ANALOG_MAX / 2;
+
+    fn direction(calibration: &Calibration, x_value: u16, y_value: u16) -> Direction {
+        let (x, y) = calibration.displacement(x_value, y_value);
+        determine_direction(x, y, 70, Direction::NoDirection)
+    }
+
+    #[test]
+    fn displacement_is_relative_to_the_center() {
+        let calibration = Calibration { center_x: 400, center_y: 600, ..Calibration::default() };
+        assert!(calibration.displacement(400, 600) == (0, 0));
+        assert!(calibration.displacement(0, ANALOG_MAX) == (-100, 100));
+        assert!(calibration.displacement(200, 300) == (-50, -50));
+        assert!(calibration.displacement(ANALOG_MAX, 0) == (100, -100));
+    }
+
+    #[test]
+    fn small_pushes_dont_orient_the_stick() {
+        let mut calibration = Calibration::default();
+        assert!(!calibration.orient(CENTER, CENTER + 300));
+        assert!(!calibration.swap_axes && !calibration.invert_x && !calibration.invert_y);
+    }
+
+    #[test]
+    fn stick_is_oriented_by_pushing_it_up() {
+        // The readings of a push up, right, down and left of a stick mounted
+        // as designed, each following one is a quarter turn further.
+        let pushes = [(0, CENTER), (CENTER, 0), (ANALOG_MAX, CENTER), (CENTER, ANALOG_MAX)];
+        let directions = [Direction::Up, Direction::Right, Direction::Down, Direction::Left];
+
+        for rotation in 0..pushes.len() {
+            let (x_value, y_value) = pushes[rotation];
+            let mut calibration = Calibration::default();
+            assert!(calibration.orient(x_value, y_value));
+
+            for (index, direction_pushed) in directions.iter().enumerate() {
+                let (x_value, y_value) = pushes[(index + rotation) % pushes.len()];
+                assert!(direction(&calibration, x_value, y_value) == *direction_pushed);
+            }
+        }
+    }
+
+    #[test]
+    fn orientation_is_saved_with_the_center() {
+        let mut storage = FakeStorage::new();
+        assert!(Calibration::load(&storage).is_none());
+
+        let mut calibration = Calibration { center_x: 480, center_y: 530, ..Calibration::default() };
+        calibration.orient(480, 0);
+        calibration.save(&mut storage);
+
+        let loaded = Calibration::load(&storage).unwrap();
+        assert!(loaded.center_x == 480 && loaded.center_y == 530);
+        assert!(loaded.invert_x && !loaded.invert_y && loaded.swap_axes);
+    }
+}
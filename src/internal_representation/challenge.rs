@@ -0,0 +1,94 @@
+// A challenge is a game which plays out alike for everyone who enters the same
+// code: the code picks the map, the speed and the cells the apples go to.
+// Challenges follow the classic rules whatever the settings, so that the
+// scores are comparable. Nothing in a challenge depends on the time, hence
+// the maps with timed hazards and the items are left out.
+
+use crate::user_interface::{MAPS_NUMBER, get_map_hazards};
+
+use super::apple_placement::AppleSequence;
+use super::entropy::EntropyPool;
+use super::game_state::GameSpeed;
+use super::hazard::Hazard;
+use super::item::ItemOdds;
+use super::rules::{Rules, Preset};
+
+// The code is entered as four hexadecimal digits.
+pub const CODE_DIGITS: usize = 4;
+
+#[derive(Copy, Clone, PartialEq)]
+pub struct Challenge {
+    code: u16,
+}
+
+impl Challenge {
+    pub fn new(code: u16) -> Challenge {
+        Challenge { code }
+    }
+
+    pub fn code(&self) -> u16 {
+        self.code
+    }
+
+    // Codes which differ in a single digit still lead to unrelated games.
+    pub fn seed(&self) -> u64 {
+        let mut pool = EntropyPool::new();
+        pool.mix(self.code as u32);
+        pool.draw_seed()
+    }
+
+    pub fn map_index(&self) -> usize {
+        let is_eligible = |index: &usize| !get_map_hazards(*index).iter().any(Hazard::is_timed);
+        let count = (0..MAPS_NUMBER).filter(is_eligible).count();
+        let pick = ((self.seed() >> 8) % count as u64) as usize;
+        (0..MAPS_NUMBER).filter(is_eligible).nth(pick).unwrap_or(0)
+    }
+
+    pub fn game_speed(&self) -> GameSpeed {
+        match self.seed() % 3 {
+            0 => GameSpeed::Slow,
+            1 => GameSpeed::Normal,
+            _ => GameSpeed::Fast,
+        }
+    }
+
+    pub fn rules(&self) -> Rules {
+        Rules { item_odds: ItemOdds::NONE, ..Preset::Classic.rules() }
+    }
+
+    pub fn apple_sequence(&self) -> AppleSequence {
+        AppleSequence::new(self.seed())
+    }
+}
+
+impl Default for Challenge {
+    fn default() -> Self {
+        Challenge::new(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn challenges_dont_depend_on_the_time() {
+        for code in 0..=u16::MAX {
+            let challenge = Challenge::new(code);
+            assert!(!get_map_hazards(challenge.map_index()).iter().any(Hazard::is_timed));
+            assert!(challenge.rules().item_odds == ItemOdds::NONE);
+        }
+    }
+
+    #[test]
+    fn every_untimed_map_gets_picked() {
+        let mut is_picked = [false; MAPS_NUMBER];
+        for code in 0..1000 {
+            is_picked[Challenge::new(code).map_index()] = true;
+        }
+        for index in 0..MAPS_NUMBER {
+            let is_timed = get_map_hazards(index).iter().any(Hazard::is_timed);
+            assert!(is_picked[index] == !is_timed);
+        }
+    }
+}
@@ -1,23 +1,39 @@
-use super::direction::Direction;
+use super::button::ButtonEvent;
+use super::direction::{Direction, Turn};
 
 pub struct ControllerInput {
-    pub toggle_signal: bool,
-    pub direction: Direction
+    pub button: Option<ButtonEvent>,
+    pub direction: Direction,
+    pub turn: Option<Turn>,
 }
 
 impl ControllerInput {
-    pub fn new(toggle_signal: bool, direction: Direction) -> ControllerInput {
-        ControllerInput { toggle_signal, direction }
+    pub fn new(button: Option<ButtonEvent>, direction: Direction) -> ControllerInput {
+        ControllerInput { button, direction, turn: None }
     }
 
-    pub fn reset_signal(&mut self) {
-        self.toggle_signal = false;
+    // Used by controllers which steer the snake by turning it left or right.
+    pub fn with_turn(button: Option<ButtonEvent>, turn: Option<Turn>) -> ControllerInput {
+        ControllerInput { button, direction: Direction::NoDirection, turn }
+    }
+
+    pub fn reset_button(&mut self) {
+        self.button = None;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.button.is_some() ||
+        self.direction != Direction::NoDirection ||
+        self.turn.is_some()
+    }
+
+    pub fn is_button(&self, event: ButtonEvent) -> bool {
+        self.button == Some(event)
     }
 }
 
 impl Default for ControllerInput {
     fn default() -> Self {
-        Self { toggle_signal: false, direction: Direction::NoDirection }
+        Self { button: None, direction: Direction::NoDirection, turn: None }
     }
 }
-
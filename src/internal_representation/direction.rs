@@ -7,6 +7,19 @@ pub enum Direction {
     NoDirection
 }
 
+// Turns are relative to the direction the snake is heading in.
+#[derive(Copy, Clone, PartialEq)]
+pub enum Turn {
+    Left,
+    Right,
+}
+
+impl Default for Direction {
+    fn default() -> Self {
+        Self::NoDirection
+    }
+}
+
 impl Direction {
     pub fn get_opposite(direction: Direction) -> Direction {
         match direction {
@@ -17,4 +30,18 @@ impl Direction {
             Direction::NoDirection => Direction::NoDirection,
         }
     }
+
+    pub fn turned(&self, turn: Turn) -> Direction {
+        match (self, turn) {
+            (Direction::Up, Turn::Left)     => Direction::Left,
+            (Direction::Up, Turn::Right)    => Direction::Right,
+            (Direction::Down, Turn::Left)   => Direction::Right,
+            (Direction::Down, Turn::Right)  => Direction::Left,
+            (Direction::Left, Turn::Left)   => Direction::Down,
+            (Direction::Left, Turn::Right)  => Direction::Up,
+            (Direction::Right, Turn::Left)  => Direction::Up,
+            (Direction::Right, Turn::Right) => Direction::Down,
+            (Direction::NoDirection, _)     => Direction::NoDirection,
+        }
+    }
 }
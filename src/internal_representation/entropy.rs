@@ -0,0 +1,118 @@
+// The console has no source of true randomness, so the seed of the random
+// generator is gathered from several weak sources which are stirred into a
+// pool: the noise of a floating analog pin, the timing of the player's input
+// down to the microsecond and a boot counter kept in the storage. The latter
+// makes sure that even a board with a quiet pin doesn't repeat itself on
+// each boot.
+
+use crate::common::BOOT_COUNTER_ADDRESS;
+use crate::traits::Storage;
+
+use super::record::{load_record, save_record};
+
+const BOOT_COUNTER_VERSION: u8 = 1;
+
+// The pool mixes the values in the manner of the FNV-1a hash.
+const FNV_OFFSET_BASIS: u32 = 0x811C_9DC5;
+const FNV_PRIME: u32 = 0x0100_0193;
+
+pub struct EntropyPool {
+    state: u32,
+    seeds_drawn: u32,
+}
+
+impl EntropyPool {
+    pub fn new() -> EntropyPool {
+        EntropyPool {
+            state: FNV_OFFSET_BASIS,
+            seeds_drawn: 0,
+        }
+    }
+
+    pub fn mix(&mut self, value: u32) {
+        for byte in value.to_le_bytes().iter() {
+            self.state = (self.state ^ *byte as u32).wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    // Each seed drawn differs from the previous ones, even if nothing was
+    // mixed in since.
+    pub fn draw_seed(&mut self) -> u64 {
+        self.seeds_drawn = self.seeds_drawn.wrapping_add(1);
+        self.mix(self.seeds_drawn);
+        finalize(self.state) as u64
+    }
+}
+
+// Collects the least significant bit of each reading into a word, that's
+// where the noise of an analog pin is.
+pub fn gather_noise(mut read_sample: impl FnMut() -> u16) -> u32 {
+    (0..u32::BITS).fold(0, |noise, _| (noise << 1) | (read_sample() & 1) as u32)
+}
+
+// Returns the number of times the console has been switched on, this one
+// included.
+pub fn count_boot(storage: &mut dyn Storage) -> u32 {
+    let mut data = [0; 4];
+    let boots = match load_record(storage, BOOT_COUNTER_ADDRESS, BOOT_COUNTER_VERSION, &mut data) {
+        true  => u32::from_le_bytes(data).wrapping_add(1),
+        false => 1,
+    };
+    save_record(storage, BOOT_COUNTER_ADDRESS, BOOT_COUNTER_VERSION, &boots.to_le_bytes());
+    boots
+}
+
+// Spreads every bit of the state over the whole seed, the finalizer of the
+// MurmurHash3 hash.
+fn finalize(mut state: u32) -> u32 {
+    state ^= state >> 16;
+    state = state.wrapping_mul(0x85EB_CA6B);
+    state ^= state >> 13;
+    state = state.wrapping_mul(0xC2B2_AE35);
+    state ^ (state >> 16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal_representation::record::FakeStorage;
+
+    #[test]
+    fn boots_are_counted_across_restarts() {
+        let mut storage = FakeStorage::new();
+        assert!(count_boot(&mut storage) == 1);
+        assert!(count_boot(&mut storage) == 2);
+        assert!(count_boot(&mut storage) == 3);
+
+        // A corrupted counter starts over.
+        storage.bytes[BOOT_COUNTER_ADDRESS as usize + 1] ^= 0x10;
+        assert!(count_boot(&mut storage) == 1);
+        assert!(count_boot(&mut storage) == 2);
+    }
+
+    #[test]
+    fn consecutive_seeds_differ() {
+        let mut pool = EntropyPool::new();
+        pool.mix(42);
+        let seeds: Vec<u64> = (0..8).map(|_| pool.draw_seed()).collect();
+        for (i, seed) in seeds.iter().enumerate() {
+            assert!(!seeds[i + 1..].contains(seed));
+        }
+
+        // The same input gives the same seeds, the pool holds no other state.
+        let mut other = EntropyPool::new();
+        other.mix(42);
+        assert!(other.draw_seed() == seeds[0]);
+    }
+
+    #[test]
+    fn only_the_lowest_bit_of_the_samples_is_noise() {
+        let mut samples = [0x3FEu16, 0x201, 0x0FF, 0x100].iter().cycle();
+        let noise = gather_noise(|| *samples.next().unwrap());
+        assert!(noise == 0x6666_6666);
+
+        assert!(gather_noise(|| 0xFFFE) == 0);
+        assert!(gather_noise(|| 0x0001) == u32::MAX);
+    }
+}
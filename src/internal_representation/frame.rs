@@ -0,0 +1,63 @@
+// Frame is a bit-packed picture of the 8x8 matrix screen. It is what gets
+// passed to the view and what all user interface pictures evaluate to.
+
+use core::ops::BitOr;
+
+use crate::common::BOARD_SIZE;
+
+/*
+ A frame is encoded as an array of eight unsigned integers, each of them
+ represents a given row of the matrix. Within a row, the most significant bit
+ corresponds to the leftmost column, so the following row: 10110111 lights up
+ the columns 0, 2, 3, 5, 6 and 7. It is the same encoding that the pictures
+ in the user interface use, which allows for using them directly.
+*/
+#[derive(Copy, Clone, PartialEq)]
+pub struct Frame {
+    rows: [u8; BOARD_SIZE],
+}
+
+impl Frame {
+    pub const fn new(rows: [u8; BOARD_SIZE]) -> Frame {
+        Frame { rows }
+    }
+
+    pub fn is_lit(&self, x: usize, y: usize) -> bool {
+        self.rows[y] & column_mask(x) != 0
+    }
+
+    pub fn light_up(&mut self, x: usize, y: usize) {
+        self.rows[y] |= column_mask(x);
+    }
+
+    pub fn get_row(&self, y: usize) -> u8 {
+        self.rows[y]
+    }
+
+    pub fn set_row(&mut self, y: usize, row: u8) {
+        self.rows[y] = row;
+    }
+}
+
+// Combines two frames, lighting up the leds lit in either of them.
+impl BitOr for Frame {
+    type Output = Frame;
+
+    fn bitor(self, other: Frame) -> Frame {
+        let mut rows = self.rows;
+        for (row, other_row) in rows.iter_mut().zip(other.rows.iter()) {
+            *row |= *other_row;
+        }
+        Frame { rows }
+    }
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Self { rows: [0; BOARD_SIZE] }
+    }
+}
+
+fn column_mask(x: usize) -> u8 {
+    0b_1000_0000 >> x
+}
@@ -3,6 +3,8 @@
 
 use crate::common::BOARD_SIZE;
 
+use super::frame::Frame;
+use super::item::Item;
 use super::point::Point;
 
 // The first column of the matrix doesn't work, hence we restrict the x range.
@@ -11,14 +13,31 @@ pub const X_UPPER_BOUND: usize = 7;
 pub const Y_LOWER_BOUND: usize = 0;
 pub const Y_UPPER_BOUND: usize = 7;
 
+// Each cell of the board is packed into three bits of its row, this way the
+// whole board takes up 32 bytes instead of 64.
+const BITS_PER_CELL: usize = 3;
+const CELL_MASK: u32 = 0b_111;
+
+// The number of phases of a blink cycle, see Item::blink_pattern.
+pub const BLINK_PHASES: u8 = 8;
+
 pub struct GameBoard {
-    board: [[BoardCell; BOARD_SIZE]; BOARD_SIZE],
+    rows: [u32; BOARD_SIZE],
 }
 
 impl GameBoard {
-    pub fn new(board: [[BoardCell; BOARD_SIZE]; BOARD_SIZE]) -> GameBoard {
-        GameBoard { board }
+    pub fn new(obstacles: Frame) -> GameBoard {
+        let mut board = GameBoard::default();
+        for y in 0..BOARD_SIZE {
+            for x in 0..BOARD_SIZE {
+                if obstacles.is_lit(x, y) {
+                    board.write_cell(x, y, BoardCell::Obstacle);
+                }
+            }
+        }
+        board
     }
+
     pub fn is_within_bounds(&self, point: Point) -> bool {
         let x = point.x as usize;
         let y = point.y as usize;
@@ -26,6 +45,13 @@ impl GameBoard {
         Y_LOWER_BOUND <= y && y <= Y_UPPER_BOUND
     }
 
+    // Brings a point which left the board back in on the opposite side.
+    pub fn wrap(&self, point: Point) -> Point {
+        Point::new(
+            wrap_coordinate(point.x, X_LOWER_BOUND, X_UPPER_BOUND),
+            wrap_coordinate(point.y, Y_LOWER_BOUND, Y_UPPER_BOUND))
+    }
+
     pub fn add_apple(&mut self, point: Point) {
         self.update_board_entry(point, BoardCell::Apple);
     }
@@ -39,31 +65,58 @@ impl GameBoard {
     }
 
     pub fn update_board_entry(&mut self, point: Point, value: BoardCell) {
-        self.board[point.y as usize][point.x as usize] = value;
+        self.write_cell(point.x as usize, point.y as usize, value);
     }
 
-    pub fn read_board_at(&mut self, point: Point) -> BoardCell {
-        self.board[point.y as usize][point.x as usize]
+    pub fn read_board_at(&self, point: Point) -> BoardCell {
+        self.read_cell(point.x as usize, point.y as usize)
     }
 
-    pub fn get_screen(&self) -> [[BoardCell; BOARD_SIZE]; BOARD_SIZE] {
-        self.board
+    // The items blink, the phase of the blink cycle picks which of them are
+    // lit on this screen.
+    pub fn get_screen(&self, blink_phase: u8) -> Frame {
+        let phase_mask = 1 << (blink_phase % BLINK_PHASES);
+        let mut screen = Frame::default();
+        for y in 0..BOARD_SIZE {
+            for x in 0..BOARD_SIZE {
+                if self.read_cell(x, y).blink_pattern() & phase_mask != 0 {
+                    screen.light_up(x, y);
+                }
+            }
+        }
+        screen
     }
 
     pub fn reset(&mut self) {
-        self.board = Default::default();
+        *self = Default::default();
+    }
+
+    fn read_cell(&self, x: usize, y: usize) -> BoardCell {
+        BoardCell::from_bits((self.rows[y] >> (x * BITS_PER_CELL)) & CELL_MASK)
+    }
+
+    fn write_cell(&mut self, x: usize, y: usize, value: BoardCell) {
+        let shift = x * BITS_PER_CELL;
+        self.rows[y] = (self.rows[y] & !(CELL_MASK << shift))
+            | (value.to_bits() << shift);
     }
 }
 
+fn wrap_coordinate(value: i8, lower_bound: usize, upper_bound: usize) -> i8 {
+    let lower_bound = lower_bound as i8;
+    let range = upper_bound as i8 - lower_bound + 1;
+    (value - lower_bound).rem_euclid(range) + lower_bound
+}
+
 impl Default for GameBoard {
     fn default() -> Self {
-        Self { board: Default::default() }
+        Self { rows: [0; BOARD_SIZE] }
     }
 }
 
 #[derive(Copy, Clone, PartialEq)]
 pub enum BoardCell {
-    Apple, Snake, Empty, Obstacle,
+    Apple, Snake, Empty, Obstacle, Item(Item),
 }
 
 impl Default for BoardCell {
@@ -75,4 +128,39 @@ impl BoardCell {
     pub fn is_empty(&self) -> bool {
         *self == Self::Empty
     }
+
+    fn blink_pattern(&self) -> u8 {
+        match self {
+            BoardCell::Empty      => 0b_0000_0000,
+            BoardCell::Item(item) => item.blink_pattern(),
+            _                     => 0b_1111_1111,
+        }
+    }
+
+    // Empty cells need to be encoded as zero so that a zeroed board is empty.
+    fn to_bits(&self) -> u32 {
+        match self {
+            BoardCell::Empty                   => 0b_000,
+            BoardCell::Apple                   => 0b_001,
+            BoardCell::Snake                   => 0b_010,
+            BoardCell::Obstacle                => 0b_011,
+            BoardCell::Item(Item::GoldenApple) => 0b_100,
+            BoardCell::Item(Item::ShrinkPill)  => 0b_101,
+            BoardCell::Item(Item::SlowMotion)  => 0b_110,
+            BoardCell::Item(Item::Ghost)       => 0b_111,
+        }
+    }
+
+    fn from_bits(bits: u32) -> BoardCell {
+        match bits {
+            0b_001 => BoardCell::Apple,
+            0b_010 => BoardCell::Snake,
+            0b_011 => BoardCell::Obstacle,
+            0b_100 => BoardCell::Item(Item::GoldenApple),
+            0b_101 => BoardCell::Item(Item::ShrinkPill),
+            0b_110 => BoardCell::Item(Item::SlowMotion),
+            0b_111 => BoardCell::Item(Item::Ghost),
+            _      => BoardCell::Empty,
+        }
+    }
 }
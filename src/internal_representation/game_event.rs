@@ -0,0 +1,33 @@
+// Events emitted by the game engine to let the other subsystems (sound,
+// animations, statistics) know what happened without the engine having to
+// drive them directly.
+
+use super::game_state::{OperationMode, GameSpeed};
+use super::settings::Settings;
+use super::item::Item;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum GameEvent {
+    GameStarted { map_index: usize, speed: GameSpeed },
+    AppleEaten,
+    GraceUsed,
+    ItemCollected { item: Item },
+    Died { cause: DeathCause },
+    Won,
+    TimeUp,
+    GameEnded { longest_snake: u8 },
+    ModeChanged { from: OperationMode, to: OperationMode },
+    SpeedChanged { speed: GameSpeed },
+    MapChanged { index: usize },
+    MenuNavigated,
+    SettingsChanged { settings: Settings },
+    WentToSleep,
+    WokeUp, // Also emitted when the console is switched on.
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum DeathCause {
+    Wall,
+    Snake,
+    Obstacle,
+}
@@ -1,59 +1,138 @@
-use crate::libs::time_util::millis;
+use crate::libs::time::{Instant, Duration};
+use crate::libs::time_util::now;
 use crate::common::{SNAKE_MOVE_INTERVAL_NORMAL, SNAKE_MOVE_INTERVAL_SLOW, SNAKE_MOVE_INTERVAL_FAST};
 
+use super::item::SLOW_MOTION_FACTOR;
+
 pub struct GameState {
-    pub score: u8,
-    last_move_timestamp: u32,
+    pub score: u16,
+    pub game_mode: GameMode,
+    best_scores: [u16; GAME_MODES_NUMBER],
+    pub apples_eaten: u8,
+    pub longest_snake: u8,
+    last_move_timestamp: Instant,
     pub mode: OperationMode,
+    mode_entered_at: Instant,
     pub game_speed: GameSpeed,
-    pub is_grace_pending: bool,
+    pub grace_moves_used: u8,
+    // The segments the snake still has to grow by after eating apples.
+    pub pending_growth: u8,
+    pub is_won: bool,
+    pub slow_motion_until: Instant,
+    pub ghost_moves_left: u8,
+    pub time_attack_ends_at: Instant,
 }
 
 impl GameState {
     pub fn new() -> GameState {
         GameState {
             score: 0,
-            last_move_timestamp: millis(),
-            mode: OperationMode::InMenu,
-            game_speed: GameSpeed::Normal,
-            is_grace_pending: false,
+            game_mode: GameMode::Classic,
+            best_scores: [0; GAME_MODES_NUMBER],
+            apples_eaten: 0,
+            longest_snake: 0,
+            last_move_timestamp: now(),
+            mode: OperationMode::Boot,
+            mode_entered_at: now(),
+            game_speed: GameSpeed::default(),
+            grace_moves_used: 0,
+            pending_growth: 0,
+            is_won: false,
+            slow_motion_until: now(),
+            ghost_moves_left: 0,
+            time_attack_ends_at: now(),
         }
     }
 
     pub fn is_time_for_next_move(&self) -> bool {
-        millis() - self.last_move_timestamp >= self.game_speed.move_duration()
+        let now = now();
+        let mut move_duration = self.game_speed.move_duration();
+        if now < self.slow_motion_until {
+            move_duration = move_duration * SLOW_MOTION_FACTOR;
+        }
+        now - self.last_move_timestamp >= move_duration
     }
 
-    pub fn register_move_at(&mut self, time: u32) {
+    pub fn enter_mode(&mut self, mode: OperationMode) {
+        self.mode = mode;
+        self.mode_entered_at = now();
+    }
+
+    pub fn time_in_mode(&self) -> Duration {
+        now() - self.mode_entered_at
+    }
+
+    pub fn register_move_at(&mut self, time: Instant) {
         self.last_move_timestamp = time;
     }
 
     pub fn start(&mut self) {
-        self.last_move_timestamp = millis();
+        self.last_move_timestamp = now();
         self.score = 0;
-        self.mode = OperationMode::GameRunning;
+        self.apples_eaten = 0;
+        self.longest_snake = 0;
+        self.grace_moves_used = 0;
+        self.pending_growth = 0;
+        self.is_won = false;
+        self.slow_motion_until = now();
+        self.ghost_moves_left = 0;
     }
 
-    pub fn return_to_menu(&mut self) {
-        self.mode = OperationMode::InMenu;
+    pub fn reset_grace(&mut self) {
+        self.grace_moves_used = 0;
     }
 
-    pub fn reset_grace(&mut self) {
-        self.is_grace_pending = false;
+    // Each game mode keeps its own best score, indexed by the mode.
+    pub fn best_scores(&self) -> [u16; GAME_MODES_NUMBER] {
+        self.best_scores
+    }
+
+    pub fn record_score(&mut self) {
+        let best_score = &mut self.best_scores[self.game_mode as usize];
+        *best_score = (*best_score).max(self.score);
     }
 }
 
+#[derive(Copy, Clone, PartialEq)]
 pub enum OperationMode {
-    GameRunning, InMenu, SelectingMap
+    Boot,
+    Menu,
+    MapSelect,
+    Countdown,
+    Playing,
+    Paused,
+    GameOver,
+    HighScores,
+    Settings,
+    Statistics,
+    CodeEntry,
+}
+
+pub const GAME_MODES_NUMBER: usize = 3;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum GameMode {
+    Classic,
+    TimeAttack, // As many apples as possible before the time runs out.
+    Challenge,  // A classic game set up by a code.
 }
 
-#[derive(Copy, Clone)]
+pub const GAME_MODES: [GameMode; GAME_MODES_NUMBER] =
+    [GameMode::Classic, GameMode::TimeAttack, GameMode::Challenge];
+
+#[derive(Copy, Clone, PartialEq)]
 pub enum GameSpeed {
     Slow, Normal, Fast
 }
 
+impl Default for GameSpeed {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
 impl GameSpeed {
-    pub fn move_duration(&self) -> u32 {
+    pub fn move_duration(&self) -> Duration {
         match self {
             GameSpeed::Slow   => SNAKE_MOVE_INTERVAL_SLOW,
             GameSpeed::Normal => SNAKE_MOVE_INTERVAL_NORMAL,
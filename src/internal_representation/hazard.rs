@@ -0,0 +1,229 @@
+// Hazards are the moving parts of a map. Each of them is described by where
+// it goes and at what pace, the cells it covers at any point of the game
+// follow from the time played and the number of apples eaten.
+
+use crate::common::BOARD_SIZE;
+use crate::libs::time::{Instant, Duration};
+use crate::libs::time_util::now;
+
+use super::frame::Frame;
+
+#[derive(Copy, Clone)]
+pub enum Hazard {
+    // A single obstacle which walks back and forth between two columns of a
+    // row, one cell per step.
+    Patrol { row: u8, from: u8, to: u8, step: Duration },
+    // A wall within a row which closes and opens again after each interval.
+    // The columns are a bitmask with the same encoding as the map rows.
+    Gate { row: u8, columns: u8, interval: Duration },
+    // A wall which moves down one row every few apples and starts over at
+    // the first row after the last one.
+    ShiftingWall { columns: u8, from_row: u8, to_row: u8, apples_per_shift: u8 },
+}
+
+impl Hazard {
+    pub fn cells(&self, time_played: Duration, apples_eaten: u8) -> Frame {
+        let mut cells = Frame::default();
+        match *self {
+            Hazard::Patrol { row, from, to, step } => {
+                let steps = time_played.as_millis() / step.as_millis().max(1);
+                cells.light_up(patrol_column(from, to, steps) as usize, row as usize);
+            },
+            Hazard::Gate { row, columns, interval } => {
+                let intervals = time_played.as_millis() / interval.as_millis().max(1);
+                if intervals % 2 == 0 {
+                    light_up_columns(&mut cells, row, columns);
+                }
+            },
+            Hazard::ShiftingWall { columns, from_row, to_row, apples_per_shift } => {
+                let shifts = apples_eaten / apples_per_shift.max(1);
+                let rows = to_row - from_row + 1;
+                light_up_columns(&mut cells, from_row + shifts % rows, columns);
+            },
+        }
+        cells
+    }
+
+    // Whether the cells the hazard covers change with the time played rather
+    // than with the apples eaten.
+    pub fn is_timed(&self) -> bool {
+        match *self {
+            Hazard::Patrol { .. }       |
+            Hazard::Gate { .. }         => true,
+            Hazard::ShiftingWall { .. } => false,
+        }
+    }
+
+    // All the cells the hazard may ever cover, the apples stay out of them.
+    pub fn reach(&self) -> Frame {
+        let mut cells = Frame::default();
+        match *self {
+            Hazard::Patrol { row, from, to, .. } => {
+                for x in from..=to {
+                    cells.light_up(x as usize, row as usize);
+                }
+            },
+            Hazard::Gate { row, columns, .. } => light_up_columns(&mut cells, row, columns),
+            Hazard::ShiftingWall { columns, from_row, to_row, .. } => {
+                for y in from_row..=to_row {
+                    light_up_columns(&mut cells, y, columns);
+                }
+            },
+        }
+        cells
+    }
+}
+
+// The cells covered by all the hazards of a map together.
+pub fn hazard_cells(hazards: &[Hazard], time_played: Duration, apples_eaten: u8) -> Frame {
+    hazards.iter()
+        .fold(Frame::default(), |cells, hazard| cells | hazard.cells(time_played, apples_eaten))
+}
+
+pub fn hazard_reach(hazards: &[Hazard]) -> Frame {
+    hazards.iter().fold(Frame::default(), |cells, hazard| cells | hazard.reach())
+}
+
+fn patrol_column(from: u8, to: u8, steps: u32) -> u8 {
+    let span = (to - from) as u32;
+    if span == 0 {
+        return from;
+    }
+
+    let position = steps % (2 * span);
+    let offset = if position <= span { position } else { 2 * span - position };
+    from + offset as u8
+}
+
+fn light_up_columns(cells: &mut Frame, row: u8, columns: u8) {
+    for x in 0..BOARD_SIZE {
+        if columns & (0b_1000_0000 >> x) != 0 {
+            cells.light_up(x, row as usize);
+        }
+    }
+}
+
+// The hazards of the map being played along with the cells they cover.
+pub struct Hazards {
+    hazards: &'static [Hazard],
+    cells: Frame,
+    reach: Frame,
+    started_at: Instant,
+}
+
+impl Hazards {
+    pub fn new(hazards: &'static [Hazard]) -> Hazards {
+        Hazards {
+            hazards,
+            cells: Frame::default(),
+            reach: hazard_reach(hazards),
+            started_at: now(),
+        }
+    }
+
+    pub fn cells(&self) -> Frame {
+        self.cells
+    }
+
+    pub fn reach(&self) -> Frame {
+        self.reach
+    }
+
+    // Returns whether any of the hazards moved.
+    pub fn update(&mut self, apples_eaten: u8) -> bool {
+        let cells = hazard_cells(self.hazards, now() - self.started_at, apples_eaten);
+        let has_moved = cells != self.cells;
+        self.cells = cells;
+        has_moved
+    }
+
+    // The hazards stand still while the game is paused.
+    pub fn postpone(&mut self, pause: Duration) {
+        self.started_at += pause;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::libs::time_util::{set_now, advance};
+
+    const PATROL: [Hazard; 1] = [
+        Hazard::Patrol { row: 2, from: 1, to: 3, step: Duration::from_millis(100) },
+    ];
+
+    fn at(millis: u32) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn lit(cells: &[(usize, usize)]) -> Frame {
+        let mut frame = Frame::default();
+        for &(x, y) in cells {
+            frame.light_up(x, y);
+        }
+        frame
+    }
+
+    #[test]
+    fn patrols_walk_back_and_forth() {
+        let patrol = PATROL[0];
+        let columns = [1, 2, 3, 2, 1, 2];
+        for (step, &x) in columns.iter().enumerate() {
+            assert!(patrol.cells(at(100 * step as u32), 0) == lit(&[(x, 2)]));
+        }
+        assert!(patrol.cells(at(150), 0) == lit(&[(2, 2)]));
+        assert!(patrol.reach() == lit(&[(1, 2), (2, 2), (3, 2)]));
+
+        let guard = Hazard::Patrol { row: 4, from: 5, to: 5, step: at(100) };
+        assert!(guard.cells(at(0), 0) == lit(&[(5, 4)]));
+        assert!(guard.cells(at(700), 0) == lit(&[(5, 4)]));
+    }
+
+    #[test]
+    fn gates_close_and_open_again() {
+        let gate = Hazard::Gate { row: 5, columns: 0b_00011000, interval: at(2000) };
+        let closed = lit(&[(3, 5), (4, 5)]);
+        assert!(gate.cells(at(0), 0) == closed);
+        assert!(gate.cells(at(1999), 0) == closed);
+        assert!(gate.cells(at(2000), 0) == Frame::default());
+        assert!(gate.cells(at(4000), 0) == closed);
+        assert!(gate.reach() == closed);
+    }
+
+    #[test]
+    fn shifting_walls_move_down_with_the_apples() {
+        let wall = Hazard::ShiftingWall {
+            columns: 0b_01100000, from_row: 2, to_row: 4, apples_per_shift: 3 };
+        assert!(wall.cells(at(0), 0) == lit(&[(1, 2), (2, 2)]));
+        assert!(wall.cells(at(9000), 2) == lit(&[(1, 2), (2, 2)]));
+        assert!(wall.cells(at(0), 3) == lit(&[(1, 3), (2, 3)]));
+        assert!(wall.cells(at(0), 6) == lit(&[(1, 4), (2, 4)]));
+        // It starts over at the first row after the last one.
+        assert!(wall.cells(at(0), 9) == lit(&[(1, 2), (2, 2)]));
+        assert!(wall.reach() == lit(&[(1, 2), (2, 2), (1, 3), (2, 3), (1, 4), (2, 4)]));
+    }
+
+    #[test]
+    fn hazards_report_when_they_move() {
+        set_now(Instant::from_millis(10_000));
+        let mut hazards = Hazards::new(&PATROL);
+        assert!(hazards.cells() == Frame::default());
+        assert!(hazards.reach() == PATROL[0].reach());
+
+        assert!(hazards.update(0));
+        assert!(hazards.cells() == lit(&[(1, 2)]));
+        advance(at(50));
+        assert!(!hazards.update(0));
+
+        advance(at(50));
+        assert!(hazards.update(0));
+        assert!(hazards.cells() == lit(&[(2, 2)]));
+
+        // They stand still while the game is paused.
+        advance(at(100));
+        hazards.postpone(at(100));
+        assert!(!hazards.update(0));
+        assert!(hazards.cells() == lit(&[(2, 2)]));
+    }
+}
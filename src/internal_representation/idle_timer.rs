@@ -0,0 +1,59 @@
+// Keeps track of the time since the player last touched the controller, so
+// that the console can go to sleep when it is left alone in the menu.
+
+use crate::libs::time::{Instant, Duration};
+
+pub struct IdleTimer {
+    timeout: Duration,
+    last_activity: Instant,
+}
+
+impl IdleTimer {
+    pub fn new(timeout: Duration, now: Instant) -> IdleTimer {
+        IdleTimer { timeout, last_activity: now }
+    }
+
+    pub fn register_activity(&mut self, now: Instant) {
+        self.last_activity = now;
+    }
+
+    pub fn is_idle(&self, now: Instant) -> bool {
+        now - self.last_activity >= self.timeout
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn becomes_idle_once_timeout_passes() {
+        let start = Instant::from_millis(1000);
+        let timer = IdleTimer::new(TIMEOUT, start);
+
+        assert!(!timer.is_idle(start));
+        assert!(!timer.is_idle(start + TIMEOUT - Duration::from_millis(1)));
+        assert!(timer.is_idle(start + TIMEOUT));
+    }
+
+    #[test]
+    fn activity_restarts_the_timeout() {
+        let start = Instant::from_millis(1000);
+        let mut timer = IdleTimer::new(TIMEOUT, start);
+
+        timer.register_activity(start + Duration::from_secs(50));
+        assert!(!timer.is_idle(start + TIMEOUT));
+        assert!(timer.is_idle(start + Duration::from_secs(110)));
+    }
+
+    #[test]
+    fn timeout_holds_across_counter_wraparound() {
+        let start = Instant::from_millis(u32::MAX - 10_000);
+        let timer = IdleTimer::new(TIMEOUT, start);
+
+        assert!(!timer.is_idle(start + Duration::from_secs(30)));
+        assert!(timer.is_idle(start + TIMEOUT));
+    }
+}
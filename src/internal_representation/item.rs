@@ -0,0 +1,88 @@
+// Special items which now and then appear on the board next to the apple.
+// Only one of them is on the board at a time and it disappears if the snake
+// doesn't pick it up in time.
+
+use crate::libs::time::Duration;
+
+// The number of apples a golden apple is worth, it makes the snake grow like
+// any apple.
+pub const GOLDEN_APPLE_WORTH: u8 = 3;
+// The number of tail segments the shrink pill takes away.
+pub const SHRINK_SEGMENTS: u8 = 3;
+// The time for which slow motion lasts and how much longer the snake takes
+// for each move meanwhile.
+pub const SLOW_MOTION_DURATION: Duration = Duration::from_secs(5);
+pub const SLOW_MOTION_FACTOR: u32 = 2;
+// The number of moves for which the ghost lets the snake pass through itself.
+pub const GHOST_MOVES: u8 = 10;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Item {
+    GoldenApple,
+    ShrinkPill,
+    SlowMotion,
+    Ghost,
+}
+
+const ITEMS: [Item; 4] = [Item::GoldenApple, Item::ShrinkPill, Item::SlowMotion, Item::Ghost];
+
+impl Item {
+    pub fn lifetime(&self) -> Duration {
+        match self {
+            Item::GoldenApple => Duration::from_secs(5),
+            _                 => Duration::from_secs(8),
+        }
+    }
+
+    // The matrix can't show colours, hence each item blinks in its own
+    // pattern. Each bit is one phase of the blink cycle, set if lit.
+    pub fn blink_pattern(&self) -> u8 {
+        match self {
+            Item::GoldenApple => 0b_0101_0101,
+            Item::ShrinkPill  => 0b_0000_1111,
+            Item::SlowMotion  => 0b_0011_0011,
+            Item::Ghost       => 0b_0000_0001,
+        }
+    }
+}
+
+// The percent chance of each item appearing after an apple is eaten, as long
+// as there is no other item on the board. They add up to at most 100.
+#[derive(Copy, Clone, PartialEq)]
+pub struct ItemOdds {
+    pub golden_apple: u8,
+    pub shrink_pill: u8,
+    pub slow_motion: u8,
+    pub ghost: u8,
+}
+
+impl ItemOdds {
+    pub const NONE: ItemOdds = ItemOdds {
+        golden_apple: 0,
+        shrink_pill: 0,
+        slow_motion: 0,
+        ghost: 0,
+    };
+
+    // Returns the item which appears for a roll between 0 and 99, None if
+    // no item does.
+    pub fn pick(&self, roll: u8) -> Option<Item> {
+        let mut threshold: u16 = 0;
+        for item in ITEMS.iter() {
+            threshold += self.odds(*item) as u16;
+            if (roll as u16) < threshold {
+                return Some(*item);
+            }
+        }
+        None
+    }
+
+    fn odds(&self, item: Item) -> u8 {
+        match item {
+            Item::GoldenApple => self.golden_apple,
+            Item::ShrinkPill  => self.shrink_pill,
+            Item::SlowMotion  => self.slow_motion,
+            Item::Ghost       => self.ghost,
+        }
+    }
+}
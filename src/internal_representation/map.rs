@@ -1,40 +1,57 @@
-use crate::common::{BOARD_SIZE, MAX_SCORE};
-use crate::internal_representation::game_board::BoardCell;
-use crate::libs::time_util::millis;
+use crate::internal_representation::frame::Frame;
+use crate::internal_representation::hazard::{self, Hazard};
+use crate::libs::time::{Instant, Duration};
+use crate::libs::time_util::now;
 use crate::user_interface::{self as UI, MAPS_NUMBER};
 
-const INTERACTION_INTERVAL: u32 = 500; // miliseconds
+const INTERACTION_INTERVAL: Duration = Duration::from_millis(500);
 
 pub struct Map {
     current_map_index: usize,
-    last_interaction_timestamp: u32
+    last_interaction_timestamp: Instant
 }
 impl Map {
     pub fn new() -> Map {
-        Map { current_map_index: 0, last_interaction_timestamp: millis() }
+        Map { current_map_index: 0, last_interaction_timestamp: now() }
     }
 
     pub fn is_time_for_interaction(&self) -> bool {
-        millis() - self.last_interaction_timestamp >= INTERACTION_INTERVAL
+        now() - self.last_interaction_timestamp >= INTERACTION_INTERVAL
     }
 
-    pub fn register_interaction_at(&mut self, timestamp: u32) {
+    pub fn register_interaction_at(&mut self, timestamp: Instant) {
         self.last_interaction_timestamp = timestamp;
     }
 
-    pub fn print_current_map(&self) -> [[BoardCell; BOARD_SIZE]; BOARD_SIZE] {
+    pub fn print_current_map(&self) -> Frame {
         match self.current_map_index {
             0 => UI::print_up_down_arrows(),
-            _ => self.get_current_map()
+            _ => {
+                let hazards = hazard::hazard_cells(self.get_hazards(), Duration::ZERO, 0);
+                self.get_current_map() | hazards
+            },
         }
     }
 
-    pub fn get_current_map(&self) -> [[BoardCell; BOARD_SIZE]; BOARD_SIZE] {
+    pub fn get_current_index(&self) -> usize {
+        self.current_map_index
+    }
+
+    pub fn get_current_map(&self) -> Frame {
         UI::print_map(self.current_map_index)
     }
 
-    pub fn get_max_score(&self) -> usize {
-        MAX_SCORE - count_obstacles(self.get_current_map())
+    pub fn get_hazards(&self) -> &'static [Hazard] {
+        UI::get_map_hazards(self.current_map_index)
+    }
+
+    // The weight of the points scored on the map, in percent.
+    pub fn get_difficulty(&self) -> u8 {
+        UI::get_map_difficulty(self.current_map_index)
+    }
+
+    pub fn select(&mut self, index: usize) {
+        self.current_map_index = index % MAPS_NUMBER;
     }
 
     pub fn get_previous(&mut self) {
@@ -46,12 +63,3 @@ impl Map {
         self.current_map_index = (self.current_map_index + 1) % MAPS_NUMBER;
     }
 }
-
-fn count_obstacles(map: [[BoardCell; BOARD_SIZE]; BOARD_SIZE]) -> usize {
-    let mut count: usize = 0;
-    for row in map {
-        row.iter()
-            .filter(|cell| **cell == BoardCell::Obstacle)
-            .for_each(|_| count += 1); }
-    count
-}
@@ -0,0 +1,179 @@
+// Melodies are stored in the program memory using one byte per note, the
+// upper five bits hold the pitch and the lower three bits the duration. The
+// notes are read through a function kept with the melody, so that melodies
+// held in the RAM can be played as well.
+
+use crate::libs::progmem;
+
+use super::game_state::GameSpeed;
+use crate::common::SNAKE_MOVE_INTERVAL_NORMAL;
+
+const DURATION_BITS: u8 = 3;
+const DURATION_MASK: u8 = 0b_111;
+
+// Durations are expressed in sixteenth notes.
+const DURATIONS: [u16; 8] = [1, 2, 3, 4, 6, 8, 12, 16];
+
+// Frequencies (Hz) of the fourth octave, higher octaves double them.
+const BASE_FREQUENCIES: [u16; 12] =
+    [262, 277, 294, 311, 330, 349, 370, 392, 415, 440, 466, 494];
+
+// Pitches are counted in semitones starting from C4, zero is a rest.
+pub const REST: u8 = 0;
+pub const C4: u8 = 1;
+pub const D4: u8 = 3;
+pub const E4: u8 = 5;
+pub const G4: u8 = 8;
+pub const A4: u8 = 10;
+pub const B4: u8 = 12;
+pub const C5: u8 = 13;
+pub const D5: u8 = 15;
+pub const E5: u8 = 17;
+pub const F5: u8 = 18;
+pub const G5: u8 = 20;
+pub const A5: u8 = 22;
+pub const C6: u8 = 25;
+
+pub const SIXTEENTH: u8 = 0;
+pub const EIGHTH: u8 = 1;
+pub const DOTTED_EIGHTH: u8 = 2;
+pub const QUARTER: u8 = 3;
+pub const DOTTED_QUARTER: u8 = 4;
+pub const HALF: u8 = 5;
+pub const DOTTED_HALF: u8 = 6;
+pub const WHOLE: u8 = 7;
+
+pub const fn note(pitch: u8, duration: u8) -> u8 {
+    pitch << DURATION_BITS | duration
+}
+
+#[derive(Copy, Clone)]
+pub struct Note {
+    pub frequency: u16, // Hz, zero is a rest
+    pub sixteenths: u16,
+}
+
+impl Note {
+    pub fn decode(encoded: u8) -> Note {
+        let pitch = encoded >> DURATION_BITS;
+        let frequency = match pitch {
+            REST => 0,
+            _    => {
+                let semitone = (pitch - C4) as usize;
+                BASE_FREQUENCIES[semitone % 12] << (semitone / 12)
+            }
+        };
+
+        Note { frequency, sixteenths: DURATIONS[(encoded & DURATION_MASK) as usize] }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Tune {
+    Menu,
+    Gameplay,
+}
+
+#[derive(Copy, Clone)]
+pub struct Melody {
+    notes: &'static [u8],
+    read_byte: fn(&'static [u8], usize) -> u8,
+    pub tempo: u16, // beats (quarter notes) per minute
+}
+
+impl Melody {
+    pub const fn in_progmem(notes: &'static [u8], tempo: u16) -> Melody {
+        Melody { notes, read_byte: progmem::read_byte, tempo }
+    }
+
+    pub const fn in_ram(notes: &'static [u8], tempo: u16) -> Melody {
+        Melody { notes, read_byte: read_ram_byte, tempo }
+    }
+
+    pub fn len(&self) -> usize {
+        self.notes.len()
+    }
+
+    pub fn get_note(&self, index: usize) -> Note {
+        Note::decode((self.read_byte)(self.notes, index))
+    }
+}
+
+fn read_ram_byte(bytes: &'static [u8], index: usize) -> u8 {
+    bytes[index]
+}
+
+impl Tune {
+    pub fn get_melody(&self) -> Melody {
+        match self {
+            Tune::Menu     => Melody::in_progmem(&MENU_NOTES, 100),
+            Tune::Gameplay => Melody::in_progmem(&GAMEPLAY_NOTES, 140),
+        }
+    }
+
+    // The tempo of the melody adjusted to the speed of the game, so that the
+    // music speeds up together with the snake.
+    pub fn get_tempo(&self, speed: GameSpeed) -> u16 {
+        let tempo = self.get_melody().tempo as u32;
+        (tempo * SNAKE_MOVE_INTERVAL_NORMAL.as_millis() / speed.move_duration().as_millis()) as u16
+    }
+}
+
+#[link_section = ".progmem.data"]
+static MENU_NOTES: [u8; 16] = [
+    note(E4, QUARTER), note(G4, QUARTER), note(A4, QUARTER), note(G4, QUARTER),
+    note(E4, QUARTER), note(D4, QUARTER), note(C4, HALF),
+    note(D4, QUARTER), note(E4, QUARTER), note(G4, QUARTER), note(E4, QUARTER),
+    note(D4, DOTTED_QUARTER), note(C4, EIGHTH), note(C4, HALF), note(REST, HALF),
+    note(REST, WHOLE)];
+
+#[link_section = ".progmem.data"]
+static GAMEPLAY_NOTES: [u8; 24] = [
+    note(C5, EIGHTH), note(E5, EIGHTH), note(G5, EIGHTH), note(E5, EIGHTH),
+    note(F5, EIGHTH), note(A5, EIGHTH), note(G5, QUARTER),
+    note(E5, EIGHTH), note(D5, EIGHTH), note(C5, EIGHTH), note(D5, EIGHTH),
+    note(E5, DOTTED_EIGHTH), note(D5, SIXTEENTH), note(C5, QUARTER),
+    note(G4, EIGHTH), note(B4, EIGHTH), note(D5, EIGHTH), note(B4, EIGHTH),
+    note(C5, EIGHTH), note(E5, EIGHTH), note(C6, QUARTER),
+    note(G5, SIXTEENTH), note(E5, SIXTEENTH), note(REST, DOTTED_HALF)];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notes_decode_into_pitch_and_duration() {
+        let a4 = Note::decode(note(A4, QUARTER));
+        assert!(a4.frequency == 440 && a4.sixteenths == 4);
+
+        let c6 = Note::decode(note(C6, DOTTED_EIGHTH));
+        assert!(c6.frequency == 1048 && c6.sixteenths == 3);
+
+        let rest = Note::decode(note(REST, WHOLE));
+        assert!(rest.frequency == 0 && rest.sixteenths == 16);
+
+        let durations = [SIXTEENTH, EIGHTH, DOTTED_EIGHTH, QUARTER, DOTTED_QUARTER, HALF, DOTTED_HALF, WHOLE]
+            .iter()
+            .map(|duration| Note::decode(note(C4, *duration)).sixteenths)
+            .collect::<Vec<_>>();
+        assert!(durations == [1, 2, 3, 4, 6, 8, 12, 16]);
+    }
+
+    #[test]
+    fn melody_reads_notes_through_its_reader() {
+        static NOTES: [u8; 2] = [note(C4, EIGHTH), note(E5, HALF)];
+        let melody = Melody::in_ram(&NOTES, 120);
+
+        assert!(melody.len() == 2);
+        assert!(melody.get_note(0).frequency == 262 && melody.get_note(0).sixteenths == 2);
+        assert!(melody.get_note(1).frequency == 660 && melody.get_note(1).sixteenths == 8);
+    }
+
+    #[test]
+    fn gameplay_tempo_scales_with_game_speed() {
+        let tempo = Tune::Gameplay.get_melody().tempo;
+        assert!(Tune::Gameplay.get_tempo(GameSpeed::Normal) == tempo);
+        assert!(Tune::Gameplay.get_tempo(GameSpeed::Fast) == tempo * 3 / 2);
+        assert!(Tune::Gameplay.get_tempo(GameSpeed::Slow) == tempo * 3 / 4);
+    }
+}
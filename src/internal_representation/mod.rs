@@ -1,9 +1,31 @@
 pub mod game_board;
 pub mod game_state;
-pub mod pin_state;
+pub mod button;
+pub mod quadrature;
 pub mod direction;
 pub mod controller_input;
 pub mod snake;
 pub mod point;
 pub mod map;
+pub mod frame;
+pub mod turn_queue;
+pub mod record;
+pub mod calibration;
+pub mod stick_direction;
+pub mod sound_effect;
+pub mod sound_director;
+pub mod melody;
+pub mod sequencer;
+pub mod idle_timer;
+pub mod game_event;
+pub mod state_machine;
+pub mod settings;
+pub mod rules;
+pub mod apple_placement;
+pub mod item;
+pub mod hazard;
+pub mod scoring;
+pub mod statistics;
+pub mod entropy;
+pub mod challenge;
 
@@ -0,0 +1,151 @@
+// Decoding of the two phase shifted signals of a quadrature rotary encoder.
+
+use super::direction::Turn;
+
+// The number of valid signal transitions between two detents of the knob.
+const STEPS_PER_DETENT: i8 = 4;
+
+/*
+ The two signals A and B form a two bit Gray code which goes through
+ 00 -> 01 -> 11 -> 10 -> 00 when the knob is turned clockwise and through
+ the same states backwards when it is turned anticlockwise. The table below
+ is indexed by the previous and the current state (previous << 2 | current)
+ and gives the step in the clockwise direction. Transitions which skip a
+ state are invalid (e.g. caused by contact bounce) and are ignored.
+*/
+const TRANSITIONS: [i8; 16] = [
+     0,  1, -1,  0,
+    -1,  0,  0,  1,
+     1,  0,  0, -1,
+     0, -1,  1,  0];
+
+// The decoder is updated on every sample of the signals, which needs to be
+// taken often enough not to miss a state, and hands out the detents the knob
+// was turned by whenever the controller is polled.
+pub struct QuadratureDecoder {
+    state: u8,
+    steps: i8,
+    // Clockwise detents are positive.
+    detents: i8,
+}
+
+impl QuadratureDecoder {
+    pub const fn new(a: bool, b: bool) -> QuadratureDecoder {
+        QuadratureDecoder { state: encode(a, b), steps: 0, detents: 0 }
+    }
+
+    pub fn update(&mut self, a: bool, b: bool) {
+        let state = encode(a, b);
+        self.steps += TRANSITIONS[(self.state << 2 | state) as usize];
+        self.state = state;
+
+        if self.steps >= STEPS_PER_DETENT {
+            self.steps = 0;
+            self.detents = self.detents.saturating_add(1);
+        } else if self.steps <= -STEPS_PER_DETENT {
+            self.steps = 0;
+            self.detents = self.detents.saturating_sub(1);
+        }
+    }
+
+    // Clockwise turns of the knob turn the snake right. The detents are
+    // handed out one at a time, so a quick turn by several detents isn't lost.
+    pub fn take_turn(&mut self) -> Option<Turn> {
+        if self.detents > 0 {
+            self.detents -= 1;
+            Some(Turn::Right)
+        } else if self.detents < 0 {
+            self.detents += 1;
+            Some(Turn::Left)
+        } else {
+            None
+        }
+    }
+}
+
+const fn encode(a: bool, b: bool) -> u8 {
+    (a as u8) << 1 | b as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The states of the clockwise rotation starting from a detent, as (a, b).
+    const CLOCKWISE: [(bool, bool); 4] = [(false, true), (true, true), (true, false), (false, false)];
+
+    // The position of a state within a single cycle of the Gray code.
+    fn position(state: u8) -> i8 {
+        match state {
+            0b00 => 0,
+            0b01 => 1,
+            0b11 => 2,
+            _    => 3,
+        }
+    }
+
+    fn feed<'a>(decoder: &mut QuadratureDecoder, states: impl Iterator<Item = &'a (bool, bool)>) {
+        for &(a, b) in states {
+            decoder.update(a, b);
+        }
+    }
+
+    #[test]
+    fn transitions_follow_the_gray_code() {
+        for previous in 0..4u8 {
+            for current in 0..4u8 {
+                let expected = match (position(current) - position(previous)).rem_euclid(4) {
+                    1 => 1,
+                    3 => -1,
+                    _ => 0, // No change or a skipped state.
+                };
+                assert!(TRANSITIONS[(previous << 2 | current) as usize] == expected,
+                    "transition {:02b} -> {:02b}", previous, current);
+            }
+        }
+    }
+
+    #[test]
+    fn full_cycle_is_a_single_turn() {
+        let mut decoder = QuadratureDecoder::new(false, false);
+        feed(&mut decoder, CLOCKWISE[..3].iter());
+        assert!(decoder.take_turn() == None);
+        feed(&mut decoder, CLOCKWISE[3..].iter());
+        assert!(decoder.take_turn() == Some(Turn::Right));
+        assert!(decoder.take_turn() == None);
+
+        feed(&mut decoder, CLOCKWISE.iter().rev().skip(1));
+        feed(&mut decoder, CLOCKWISE[3..].iter());
+        assert!(decoder.take_turn() == Some(Turn::Left));
+    }
+
+    #[test]
+    fn wobbling_between_states_doesnt_turn() {
+        let mut decoder = QuadratureDecoder::new(false, false);
+        for _ in 0..10 {
+            feed(&mut decoder, [(false, true), (false, false)].iter());
+        }
+        assert!(decoder.take_turn() == None);
+    }
+
+    #[test]
+    fn skipped_states_are_ignored() {
+        let mut decoder = QuadratureDecoder::new(false, false);
+        feed(&mut decoder, [(true, true), (false, false), (true, true), (false, false)].iter());
+        assert!(decoder.take_turn() == None);
+    }
+
+    #[test]
+    fn detents_are_handed_out_one_at_a_time() {
+        let mut decoder = QuadratureDecoder::new(false, false);
+        for _ in 0..3 {
+            feed(&mut decoder, CLOCKWISE.iter());
+        }
+        feed(&mut decoder, CLOCKWISE.iter().rev().skip(1));
+        feed(&mut decoder, CLOCKWISE[3..].iter());
+
+        assert!(decoder.take_turn() == Some(Turn::Right));
+        assert!(decoder.take_turn() == Some(Turn::Right));
+        assert!(decoder.take_turn() == None);
+    }
+}
@@ -0,0 +1,63 @@
+// Records are the way data is persisted in the storage. Each record starts
+// with a version byte and is followed by its data and a checksum. That way
+// erased memory, data written by an older firmware or corrupted data are all
+// detected and the caller can fall back to defaults.
+
+use crate::traits::Storage;
+
+// Erased EEPROM reads as 0xFF, it must never be used as a record version.
+const ERASED: u8 = 0xFF;
+
+pub fn load_record(
+    storage: &dyn Storage, address: u16, version: u8, data: &mut [u8]) -> bool {
+
+    if version == ERASED || storage.read_byte(address) != version {
+        return false;
+    }
+
+    for (i, byte) in data.iter_mut().enumerate() {
+        *byte = storage.read_byte(address + 1 + i as u16);
+    }
+
+    let stored_checksum = storage.read_byte(address + 1 + data.len() as u16);
+    stored_checksum == checksum(version, data)
+}
+
+pub fn save_record(
+    storage: &mut dyn Storage, address: u16, version: u8, data: &[u8]) {
+
+    storage.write_byte(address, version);
+    for (i, byte) in data.iter().enumerate() {
+        storage.write_byte(address + 1 + i as u16, *byte);
+    }
+    storage.write_byte(address + 1 + data.len() as u16, checksum(version, data));
+}
+
+fn checksum(version: u8, data: &[u8]) -> u8 {
+    data.iter().fold(version, |sum, byte| sum.rotate_left(1) ^ byte)
+}
+
+// A storage kept in memory for the tests, it reads as erased EEPROM until
+// written to.
+#[cfg(test)]
+pub struct FakeStorage {
+    pub bytes: [u8; 1024],
+}
+
+#[cfg(test)]
+impl FakeStorage {
+    pub fn new() -> FakeStorage {
+        FakeStorage { bytes: [ERASED; 1024] }
+    }
+}
+
+#[cfg(test)]
+impl Storage for FakeStorage {
+    fn read_byte(&self, address: u16) -> u8 {
+        self.bytes[address as usize]
+    }
+
+    fn write_byte(&mut self, address: u16, value: u8) -> () {
+        self.bytes[address as usize] = value;
+    }
+}
@@ -0,0 +1,109 @@
+// The rules of the game. The engine only follows the ruleset it is given, the
+// presets below are the ones the player can choose from in the settings.
+
+use super::apple_placement::PlacementPolicy;
+use super::item::ItemOdds;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum WallMode {
+    Solid,
+    Wrap, // The snake comes out on the other side of the board.
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum WinCondition {
+    FillBoard,
+    EatApples(u8),
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub struct Rules {
+    // The number of consecutive moves the snake may spend stuck before it dies.
+    pub grace_moves: u8,
+    // The number of segments the snake grows by for each apple it eats.
+    pub growth_per_apple: u8,
+    // Whether the head may enter the cell the tail is leaving in the same move.
+    pub is_tail_chasing_allowed: bool,
+    pub wall_mode: WallMode,
+    pub win_condition: WinCondition,
+    pub starting_length: u8,
+    pub apple_placement: PlacementPolicy,
+    pub item_odds: ItemOdds,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Preset {
+    Classic,
+    Casual,
+    Hardcore,
+}
+
+pub const PRESETS_NUMBER: usize = 3;
+
+impl Preset {
+    pub fn from_index(index: u8) -> Preset {
+        match index {
+            0 => Preset::Classic,
+            1 => Preset::Casual,
+            _ => Preset::Hardcore,
+        }
+    }
+
+    pub fn rules(&self) -> Rules {
+        match self {
+            Preset::Classic  => Rules::CLASSIC,
+            Preset::Casual   => Rules::CASUAL,
+            Preset::Hardcore => Rules::HARDCORE,
+        }
+    }
+}
+
+impl Rules {
+    pub const CLASSIC: Rules = Rules {
+        grace_moves: 1,
+        growth_per_apple: 1,
+        is_tail_chasing_allowed: true,
+        wall_mode: WallMode::Solid,
+        win_condition: WinCondition::FillBoard,
+        starting_length: 1,
+        apple_placement: PlacementPolicy::ANYWHERE,
+        item_odds: ItemOdds { golden_apple: 10, shrink_pill: 5, slow_motion: 5, ghost: 5 },
+    };
+
+    pub const CASUAL: Rules = Rules {
+        grace_moves: 2,
+        growth_per_apple: 1,
+        is_tail_chasing_allowed: true,
+        wall_mode: WallMode::Wrap,
+        win_condition: WinCondition::EatApples(30),
+        starting_length: 1,
+        apple_placement: PlacementPolicy { avoid_head: true, avoid_dead_ends: true },
+        item_odds: ItemOdds { golden_apple: 15, shrink_pill: 10, slow_motion: 10, ghost: 10 },
+    };
+
+    pub const HARDCORE: Rules = Rules {
+        grace_moves: 0,
+        growth_per_apple: 2,
+        is_tail_chasing_allowed: false,
+        wall_mode: WallMode::Solid,
+        win_condition: WinCondition::FillBoard,
+        starting_length: 3,
+        apple_placement: PlacementPolicy::ANYWHERE,
+        item_odds: ItemOdds { golden_apple: 10, ghost: 5, ..ItemOdds::NONE },
+    };
+
+    // A full board always ends the game as a win, as there is no room left
+    // for the next apple, the goal may end it earlier.
+    pub fn is_goal_reached(&self, apples_eaten: u8) -> bool {
+        match self.win_condition {
+            WinCondition::FillBoard       => false,
+            WinCondition::EatApples(goal) => apples_eaten >= goal,
+        }
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules::CLASSIC
+    }
+}
@@ -0,0 +1,132 @@
+// The points scored during a game. Apples and items are worth a number of
+// base points, apples eaten in quick succession earn a combo bonus on top and
+// the sum is weighted by the speed of the game and the difficulty of the map.
+
+use crate::libs::time::{Instant, Duration};
+
+use super::game_state::GameSpeed;
+
+pub const APPLE_POINTS: u16 = 10;
+// The points of the items other than the golden apple, which counts as
+// several apples.
+pub const ITEM_POINTS: u16 = 5;
+
+// The time within which the next apple needs to be eaten to keep the combo
+// going, and the bonus for each apple of the combo after the first one.
+const COMBO_WINDOW: Duration = Duration::from_secs(3);
+const COMBO_BONUS: u16 = 5;
+const MAX_COMBO: u8 = 5;
+
+pub struct Scoring {
+    combo: u8,
+    last_apple_at: Instant,
+    speed: GameSpeed,
+    map_difficulty: u8, // In percent.
+}
+
+impl Scoring {
+    pub fn new() -> Scoring {
+        Scoring {
+            combo: 0,
+            last_apple_at: Instant::default(),
+            speed: GameSpeed::default(),
+            map_difficulty: 100,
+        }
+    }
+
+    pub fn start(&mut self, speed: GameSpeed, map_difficulty: u8) {
+        *self = Scoring { speed, map_difficulty, ..Scoring::new() };
+    }
+
+    // Returns the points for eating the given number of apples at once.
+    pub fn apple_points(&mut self, apples: u8, now: Instant) -> u16 {
+        let is_combo = self.combo > 0 && now - self.last_apple_at <= COMBO_WINDOW;
+        self.combo = if is_combo { (self.combo + 1).min(MAX_COMBO) } else { 1 };
+        self.last_apple_at = now;
+
+        let bonus = (self.combo - 1) as u16 * COMBO_BONUS;
+        self.weigh(apples as u16 * APPLE_POINTS + bonus)
+    }
+
+    pub fn item_points(&self) -> u16 {
+        self.weigh(ITEM_POINTS)
+    }
+
+    // The time spent paused doesn't break the combo.
+    pub fn postpone(&mut self, pause: Duration) {
+        self.last_apple_at += pause;
+    }
+
+    fn weigh(&self, points: u16) -> u16 {
+        let weighted = points as u32
+            * speed_percent(self.speed)
+            * self.map_difficulty as u32 / (100 * 100);
+        weighted.min(u16::MAX as u32) as u16
+    }
+}
+
+fn speed_percent(speed: GameSpeed) -> u32 {
+    match speed {
+        GameSpeed::Slow   => 100,
+        GameSpeed::Normal => 150,
+        GameSpeed::Fast   => 200,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(millis: u32) -> Instant {
+        Instant::from_millis(millis)
+    }
+
+    fn scoring(speed: GameSpeed, map_difficulty: u8) -> Scoring {
+        let mut scoring = Scoring::new();
+        scoring.start(speed, map_difficulty);
+        scoring
+    }
+
+    #[test]
+    fn apples_in_quick_succession_earn_a_combo_bonus() {
+        let mut scoring = scoring(GameSpeed::Slow, 100);
+        assert!(scoring.apple_points(1, at(1000)) == 10);
+        assert!(scoring.apple_points(1, at(4000)) == 15);
+        assert!(scoring.apple_points(1, at(5000)) == 20);
+    }
+
+    #[test]
+    fn combo_ends_once_the_window_passes() {
+        let mut scoring = scoring(GameSpeed::Slow, 100);
+        scoring.apple_points(1, at(1000));
+        assert!(scoring.apple_points(1, at(4000)) == 15);
+        assert!(scoring.apple_points(1, at(7001)) == 10);
+    }
+
+    #[test]
+    fn combo_bonus_is_capped() {
+        let mut scoring = scoring(GameSpeed::Slow, 100);
+        let points: Vec<u16> = (0..7)
+            .map(|apple| scoring.apple_points(1, at(apple * 1000)))
+            .collect();
+        assert!(points == [10, 15, 20, 25, 30, 30, 30]);
+    }
+
+    #[test]
+    fn points_are_weighted_by_the_speed_and_the_map() {
+        assert!(scoring(GameSpeed::Normal, 100).apple_points(1, at(0)) == 15);
+        assert!(scoring(GameSpeed::Fast, 100).apple_points(1, at(0)) == 20);
+        assert!(scoring(GameSpeed::Normal, 140).apple_points(1, at(0)) == 21);
+        assert!(scoring(GameSpeed::Fast, 120).item_points() == 12);
+        // A golden apple counts as several apples.
+        assert!(scoring(GameSpeed::Slow, 100).apple_points(3, at(0)) == 30);
+    }
+
+    #[test]
+    fn pauses_dont_break_the_combo() {
+        let mut scoring = scoring(GameSpeed::Slow, 100);
+        scoring.apple_points(1, at(1000));
+        scoring.postpone(Duration::from_secs(10));
+        assert!(scoring.apple_points(1, at(13_000)) == 15);
+    }
+}
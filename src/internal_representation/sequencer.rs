@@ -0,0 +1,148 @@
+// The sequencer keeps track of the position within a looping melody. It is
+// advanced with the current time and reports the frequency to play whenever
+// the next note is due, so it never blocks the rendering of the screen.
+
+use crate::libs::time::{Instant, Duration};
+
+use super::melody::Melody;
+
+const MINUTE: Duration = Duration::from_secs(60);
+const SIXTEENTHS_PER_BEAT: u32 = 4;
+
+pub struct Sequencer {
+    melody: Option<Melody>,
+    position: usize,
+    tempo: u16,
+    note_started: Instant,
+    note_duration: Duration,
+}
+
+impl Sequencer {
+    pub fn new() -> Sequencer {
+        Sequencer {
+            melody: None,
+            position: 0,
+            tempo: 0,
+            note_started: Instant::default(),
+            note_duration: Duration::ZERO,
+        }
+    }
+
+    // Starts the melody from the beginning and returns the frequency of its
+    // first note.
+    pub fn start(&mut self, melody: Melody, tempo: u16, now: Instant) -> Option<u16> {
+        if melody.len() == 0 {
+            self.stop();
+            return None;
+        }
+        self.melody = Some(melody);
+        self.tempo = tempo.max(1);
+        Some(self.start_note(0, now))
+    }
+
+    pub fn stop(&mut self) {
+        self.melody = None;
+    }
+
+    // Returns the frequency of the next note if it is due, the melody loops
+    // once it reaches the end. Zero is returned for rests.
+    pub fn update(&mut self, now: Instant) -> Option<u16> {
+        let melody = self.melody?;
+
+        if now - self.note_started < self.note_duration {
+            return None;
+        }
+
+        // The next note starts when the previous one was due rather than
+        // when we got to check, so that the delays don't add up.
+        let next_start = self.note_started + self.note_duration;
+        Some(self.start_note((self.position + 1) % melody.len(), next_start))
+    }
+
+    pub fn get_current_frequency(&self) -> Option<u16> {
+        self.melody.map(|melody| melody.get_note(self.position).frequency)
+    }
+
+    fn start_note(&mut self, position: usize, now: Instant) -> u16 {
+        let note = match self.melody {
+            Some(melody) => melody.get_note(position),
+            None         => return 0,
+        };
+
+        self.position = position;
+        self.note_started = now;
+        self.note_duration = Duration::from_millis(
+            MINUTE.as_millis() * note.sixteenths as u32 / (self.tempo as u32 * SIXTEENTHS_PER_BEAT));
+        note.frequency
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal_representation::melody::{note, C4, E4, G4, REST, EIGHTH, QUARTER, HALF};
+
+    static NOTES: [u8; 4] = [note(C4, QUARTER), note(E4, EIGHTH), note(REST, EIGHTH), note(G4, HALF)];
+
+    // The frequencies reported while the time goes on in steps of 10 ms,
+    // together with the time they were reported at.
+    fn timeline(tempo: u16, until: u32) -> Vec<(u32, u16)> {
+        let mut sequencer = Sequencer::new();
+        let mut timeline = Vec::new();
+        let first = sequencer.start(Melody::in_ram(&NOTES, tempo), tempo, Instant::from_millis(0));
+        timeline.extend(first.map(|frequency| (0, frequency)));
+
+        for millis in (10..until).step_by(10) {
+            if let Some(frequency) = sequencer.update(Instant::from_millis(millis)) {
+                timeline.push((millis, frequency));
+            }
+        }
+        timeline
+    }
+
+    #[test]
+    fn notes_follow_each_other_in_time() {
+        // A quarter note takes 500 ms at 120 beats per minute.
+        assert!(timeline(120, 2000) == [(0, 262), (500, 330), (750, 0), (1000, 392)]);
+    }
+
+    #[test]
+    fn tempo_scales_the_note_durations() {
+        assert!(timeline(60, 4000) == [(0, 262), (1000, 330), (1500, 0), (2000, 392)]);
+        assert!(timeline(240, 1000) == [(0, 262), (250, 330), (380, 0), (500, 392)]);
+    }
+
+    #[test]
+    fn melody_loops_once_it_ends() {
+        assert!(timeline(120, 4100) == [
+            (0, 262), (500, 330), (750, 0), (1000, 392),
+            (2000, 262), (2500, 330), (2750, 0), (3000, 392),
+            (4000, 262)]);
+    }
+
+    #[test]
+    fn late_updates_dont_delay_the_following_notes() {
+        let mut sequencer = Sequencer::new();
+        sequencer.start(Melody::in_ram(&NOTES, 120), 120, Instant::from_millis(0));
+
+        assert!(sequencer.update(Instant::from_millis(620)) == Some(330));
+        assert!(sequencer.update(Instant::from_millis(740)) == None);
+        assert!(sequencer.update(Instant::from_millis(750)) == Some(0));
+    }
+
+    #[test]
+    fn stopped_sequencer_is_silent() {
+        let mut sequencer = Sequencer::new();
+        assert!(sequencer.update(Instant::from_millis(0)) == None);
+
+        sequencer.start(Melody::in_ram(&NOTES, 120), 120, Instant::from_millis(0));
+        assert!(sequencer.get_current_frequency() == Some(262));
+        sequencer.stop();
+        assert!(sequencer.update(Instant::from_millis(5000)) == None);
+        assert!(sequencer.get_current_frequency() == None);
+
+        static EMPTY: [u8; 0] = [];
+        assert!(sequencer.start(Melody::in_ram(&EMPTY, 120), 120, Instant::from_millis(0)) == None);
+    }
+}
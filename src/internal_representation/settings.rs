@@ -0,0 +1,253 @@
+// The options of the console which the player can change in the settings
+// menu. They are kept as indices into the list of possible values of each
+// setting, which makes them easy to browse in the menu and to store.
+
+use crate::common::{SETTINGS_ADDRESS, MAX_BRIGHTNESS};
+use crate::traits::{Storage, MenuModel, GameObserver, Runnable};
+use crate::user_interface as UI;
+
+use super::frame::Frame;
+use super::game_event::GameEvent;
+use super::game_state::GameSpeed;
+use super::rules::{Rules, Preset, WallMode, PRESETS_NUMBER};
+use super::record::{load_record, save_record};
+
+const SETTINGS_VERSION: u8 = 3;
+pub const SETTINGS_NUMBER: usize = 7;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Setting {
+    Rules,
+    WallMode,
+    Grace,
+    Brightness,
+    Sound,
+    Speed,
+    Sensitivity,
+}
+
+// The order in which the settings are listed in the menu and stored.
+const SETTINGS: [Setting; SETTINGS_NUMBER] = [
+    Setting::Rules,
+    Setting::WallMode,
+    Setting::Grace,
+    Setting::Brightness,
+    Setting::Sound,
+    Setting::Speed,
+    Setting::Sensitivity,
+];
+
+impl Setting {
+    pub fn value_count(&self) -> u8 {
+        match self {
+            Setting::Rules       => PRESETS_NUMBER as u8,
+            Setting::WallMode    => 3,
+            Setting::Grace       => 5,
+            Setting::Brightness  => MAX_BRIGHTNESS,
+            Setting::Sound       => 2,
+            Setting::Speed       => 3,
+            Setting::Sensitivity => 5,
+        }
+    }
+
+    pub fn default_value(&self) -> u8 {
+        match self {
+            Setting::Rules       => 0, // Classic.
+            Setting::WallMode    => 0, // As in the preset.
+            Setting::Grace       => 0, // As in the preset.
+            Setting::Brightness  => MAX_BRIGHTNESS - 1,
+            Setting::Sound       => 1, // On.
+            Setting::Speed       => 1, // Normal.
+            Setting::Sensitivity => 2,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub struct Settings {
+    values: [u8; SETTINGS_NUMBER],
+}
+
+impl Settings {
+    // Settings which are out of range, e.g. because they were stored by a
+    // firmware with more options, fall back to their defaults.
+    pub fn load(storage: &dyn Storage) -> Option<Settings> {
+        let mut data = [0; SETTINGS_NUMBER];
+        if !load_record(storage, SETTINGS_ADDRESS, SETTINGS_VERSION, &mut data) {
+            return None;
+        }
+
+        let mut settings = Settings::default();
+        for (index, value) in data.iter().enumerate() {
+            settings.set_value(index, *value);
+        }
+        Some(settings)
+    }
+
+    pub fn save(&self, storage: &mut dyn Storage) {
+        save_record(storage, SETTINGS_ADDRESS, SETTINGS_VERSION, &self.values);
+    }
+
+    pub fn get(&self, setting: Setting) -> u8 {
+        self.values[setting_index(setting)]
+    }
+
+    pub fn set(&mut self, setting: Setting, value: u8) {
+        self.set_value(setting_index(setting), value);
+    }
+
+    // The preset makes up the rules, the wall mode and the number of grace
+    // moves may be set on top of it. The first value of either keeps the one
+    // of the preset.
+    pub fn rules(&self) -> Rules {
+        let mut rules = Preset::from_index(self.get(Setting::Rules)).rules();
+        match self.get(Setting::WallMode) {
+            0 => (),
+            1 => rules.wall_mode = WallMode::Solid,
+            _ => rules.wall_mode = WallMode::Wrap,
+        }
+        match self.get(Setting::Grace) {
+            0     => (),
+            value => rules.grace_moves = value - 1,
+        }
+        rules
+    }
+
+    pub fn brightness(&self) -> u8 {
+        self.get(Setting::Brightness) + 1
+    }
+
+    pub fn is_sound_on(&self) -> bool {
+        self.get(Setting::Sound) != 0
+    }
+
+    pub fn game_speed(&self) -> GameSpeed {
+        match self.get(Setting::Speed) {
+            0 => GameSpeed::Slow,
+            1 => GameSpeed::Normal,
+            _ => GameSpeed::Fast,
+        }
+    }
+
+    // The higher the sensitivity the smaller the dead zone of the stick.
+    pub fn sensitivity(&self) -> u8 {
+        self.get(Setting::Sensitivity) + 1
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let mut values = [0; SETTINGS_NUMBER];
+        for (value, setting) in values.iter_mut().zip(SETTINGS.iter()) {
+            *value = setting.default_value();
+        }
+        Self { values }
+    }
+}
+
+impl MenuModel for Settings {
+    fn len(&self) -> usize {
+        SETTINGS_NUMBER
+    }
+
+    fn icon(&self, index: usize) -> Frame {
+        UI::print_setting_icon(SETTINGS[index])
+    }
+
+    fn value(&self, index: usize) -> u8 {
+        self.values[index]
+    }
+
+    fn value_count(&self, index: usize) -> u8 {
+        SETTINGS[index].value_count()
+    }
+
+    fn set_value(&mut self, index: usize, value: u8) -> () {
+        let setting = SETTINGS[index];
+        self.values[index] = if value < setting.value_count() {
+            value
+        } else {
+            setting.default_value()
+        };
+    }
+}
+
+fn setting_index(setting: Setting) -> usize {
+    SETTINGS.iter().position(|listed| *listed == setting).unwrap_or(0)
+}
+
+// Persists the settings whenever they change.
+pub struct SettingsStorage<S: Storage> {
+    storage: S,
+}
+
+impl<S: Storage> SettingsStorage<S> {
+    pub fn new(storage: S) -> Self {
+        SettingsStorage { storage }
+    }
+}
+
+impl<S: Storage> GameObserver for SettingsStorage<S> {
+    fn on_event(&mut self, event: GameEvent) -> () {
+        if let GameEvent::SettingsChanged { settings } = event {
+            settings.save(&mut self.storage);
+        }
+    }
+}
+
+impl<S: Storage> Runnable for SettingsStorage<S> {
+    fn run_once(&mut self) -> () {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal_representation::record::FakeStorage;
+
+    fn with(setting: Setting, value: u8) -> Settings {
+        let mut settings = Settings::default();
+        settings.set(setting, value);
+        settings
+    }
+
+    #[test]
+    fn rules_follow_the_preset_by_default() {
+        assert!(Settings::default().rules() == Rules::CLASSIC);
+        assert!(with(Setting::Rules, 1).rules() == Rules::CASUAL);
+        assert!(with(Setting::Rules, 2).rules() == Rules::HARDCORE);
+    }
+
+    #[test]
+    fn overrides_apply_on_top_of_the_preset() {
+        let mut settings = with(Setting::Rules, 1);
+        settings.set(Setting::WallMode, 1);
+        settings.set(Setting::Grace, 4);
+
+        let rules = settings.rules();
+        assert!(rules.wall_mode == WallMode::Solid);
+        assert!(rules.grace_moves == 3);
+        assert!(rules.growth_per_apple == Rules::CASUAL.growth_per_apple);
+        assert!(rules.win_condition == Rules::CASUAL.win_condition);
+
+        assert!(with(Setting::WallMode, 2).rules().wall_mode == WallMode::Wrap);
+        assert!(with(Setting::Grace, 1).rules().grace_moves == 0);
+    }
+
+    #[test]
+    fn settings_survive_a_round_trip_through_the_storage() {
+        let mut storage = FakeStorage::new();
+        assert!(Settings::load(&storage).is_none());
+
+        let settings = with(Setting::Grace, 3);
+        settings.save(&mut storage);
+        assert!(Settings::load(&storage) == Some(settings));
+    }
+
+    #[test]
+    fn values_out_of_range_fall_back_to_the_defaults() {
+        let mut settings = Settings::default();
+        settings.set(Setting::WallMode, 3);
+        assert!(settings.get(Setting::WallMode) == Setting::WallMode.default_value());
+    }
+}
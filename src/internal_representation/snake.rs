@@ -1,23 +1,33 @@
-use arrayvec::ArrayVec;
-
 use crate::common::BOARD_SIZE;
+use crate::libs::ring_buffer::{self, RingBuffer};
 
 use super::point::Point;
 use super::direction::Direction;
 
 pub const MAX_SNAKE_LENGTH: usize = BOARD_SIZE * BOARD_SIZE;
 
+// The left edge of the board is free on all the maps down to this length.
+pub const MAX_STARTING_LENGTH: u8 = 5;
+
+// The segments are kept in a ring buffer ordered from the tail (front) to the
+// head (back), so that moving the snake only pushes the new head and pops the
+// old tail without shifting the remaining segments.
 pub struct Snake {
-    pub segments: ArrayVec<Point, MAX_SNAKE_LENGTH>,
+    segments: RingBuffer<Point, MAX_SNAKE_LENGTH>,
     pub head: Point,
     direction: Direction,
 }
 
 impl Snake {
-    pub fn new() -> Snake {
+    // The snake starts in the top left corner heading right, the rest of its
+    // body is laid out downwards along the left edge.
+    pub fn new(length: u8) -> Snake {
         let head = Point::new(0, 0);
-        let mut segments: ArrayVec<Point, MAX_SNAKE_LENGTH> = ArrayVec::new();
-        segments.push(head);
+        let length = length.clamp(1, MAX_STARTING_LENGTH) as i8;
+        let mut segments: RingBuffer<Point, MAX_SNAKE_LENGTH> = RingBuffer::new();
+        for y in (0..length).rev() {
+            segments.push_back(Point::new(0, y)).ok();
+        }
 
         Snake { segments, head, direction: Direction::Right }
     }
@@ -31,13 +41,36 @@ impl Snake {
             Direction::NoDirection => self.head // Shouldn't be reached.
         }
     }
-    pub fn move_forward(&mut self) {
-        self.head = self.look_ahead();
-        self.segments.push(self.head);
+    // The new head is normally the cell ahead, unless the snake went through
+    // a wall and came out on the other side.
+    pub fn move_to(&mut self, head: Point) {
+        self.head = head;
+        self.segments.push_back(self.head).ok();
+    }
+
+    pub fn tail(&self) -> Point {
+        self.segments.front().unwrap_or(self.head)
     }
 
     pub fn advance_tail(&mut self) -> Point {
-        self.segments.remove(0)
+        self.segments.pop_front().unwrap_or(self.head)
+    }
+
+    pub fn occupies(&self, point: Point) -> bool {
+        self.segments.iter().any(|segment| segment == point)
+    }
+
+    pub fn get_direction(&self) -> Direction {
+        self.direction
+    }
+
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    // Iterates over the segments starting from the head and ending at the tail.
+    pub fn segments(&self) -> core::iter::Rev<ring_buffer::Iter<Point, MAX_SNAKE_LENGTH>> {
+        self.segments.iter().rev()
     }
 
     pub fn change_direction(&mut self, new_direction: Direction) {
@@ -53,5 +86,3 @@ impl Snake {
         };
     }
 }
-
-
@@ -0,0 +1,213 @@
+// Picks the sound effects and the music for the game events and leaves it to
+// the sound output to play them, which keeps the choice testable without the
+// hardware.
+
+use crate::common::MUSIC_FOLLOWS_GAME_SPEED;
+use crate::traits::{Runnable, Sound, GameObserver};
+
+use super::game_event::GameEvent;
+use super::game_state::{OperationMode, GameSpeed};
+use super::melody::Tune;
+use super::settings::Settings;
+use super::sound_effect::SoundEffect;
+
+pub struct SoundDirector<S: Sound> {
+    sound: S,
+    is_muted: bool,
+    game_speed: GameSpeed,
+}
+
+impl<S: Sound> SoundDirector<S> {
+    pub fn new(sound: S) -> SoundDirector<S> {
+        SoundDirector {
+            sound,
+            is_muted: false,
+            game_speed: GameSpeed::default(),
+        }
+    }
+
+    fn play_tune(&mut self, tune: Tune) {
+        let tempo = match tune {
+            Tune::Gameplay if MUSIC_FOLLOWS_GAME_SPEED => tune.get_tempo(self.game_speed),
+            _                                          => tune.get_melody().tempo,
+        };
+        self.sound.play_music(tune, tempo);
+    }
+
+    fn on_mode_changed(&mut self, from: OperationMode, to: OperationMode) {
+        match (from, to) {
+            (OperationMode::Boot, _)     |
+            (OperationMode::GameOver, _) => (),
+            (_, OperationMode::Playing)  => self.play_tune(Tune::Gameplay),
+            (_, OperationMode::Paused)   => {
+                self.sound.stop_music();
+                self.sound.play(SoundEffect::MenuClick);
+            },
+            // The end of the game has its own sound effects.
+            (_, OperationMode::GameOver) => self.play_tune(Tune::Menu),
+            _                            => self.sound.play(SoundEffect::MenuClick),
+        }
+    }
+
+    // Muting cuts the current sound effect short, hence it only happens when
+    // the sound setting actually changes.
+    fn on_settings_changed(&mut self, settings: Settings) {
+        self.game_speed = settings.game_speed();
+        if settings.is_sound_on() == self.is_muted {
+            self.is_muted = !settings.is_sound_on();
+            self.sound.set_muted(self.is_muted);
+        }
+    }
+}
+
+impl<S: Sound> GameObserver for SoundDirector<S> {
+    fn on_event(&mut self, event: GameEvent) -> () {
+        match event {
+            GameEvent::AppleEaten                   => self.sound.play(SoundEffect::AppleEaten),
+            GameEvent::ItemCollected { .. }         => self.sound.play(SoundEffect::ItemCollected),
+            GameEvent::GraceUsed                    => self.sound.play(SoundEffect::GraceWarning),
+            GameEvent::Died { .. }                  => self.sound.play(SoundEffect::Death),
+            GameEvent::Won                          |
+            GameEvent::TimeUp                       => self.sound.play(SoundEffect::Win),
+            GameEvent::ModeChanged { from, to }     => self.on_mode_changed(from, to),
+            GameEvent::SpeedChanged { speed }       => self.game_speed = speed,
+            GameEvent::MapChanged { .. }            |
+            GameEvent::MenuNavigated                => self.sound.play(SoundEffect::MenuClick),
+            GameEvent::SettingsChanged { settings } => self.on_settings_changed(settings),
+            GameEvent::WentToSleep                  => self.sound.stop_music(),
+            GameEvent::WokeUp                       => self.play_tune(Tune::Menu),
+            GameEvent::GameStarted { .. }           |
+            GameEvent::GameEnded { .. }             => (),
+        }
+    }
+}
+
+impl<S: Sound> Runnable for SoundDirector<S> {
+    fn run_once(&mut self) -> () {
+        self.sound.run_once();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal_representation::game_event::DeathCause;
+    use crate::internal_representation::item::Item;
+    use crate::internal_representation::settings::Setting;
+
+    #[derive(Copy, Clone, PartialEq)]
+    enum Call {
+        Play(SoundEffect),
+        PlayMusic(Tune, u16),
+        StopMusic,
+        SetMuted(bool),
+    }
+
+    // Records the calls instead of making any sound.
+    #[derive(Default)]
+    struct RecordingSound {
+        calls: Vec<Call>,
+    }
+
+    impl Sound for RecordingSound {
+        fn play(&mut self, effect: SoundEffect) -> () {
+            self.calls.push(Call::Play(effect));
+        }
+
+        fn play_music(&mut self, tune: Tune, tempo: u16) -> () {
+            self.calls.push(Call::PlayMusic(tune, tempo));
+        }
+
+        fn stop_music(&mut self) -> () {
+            self.calls.push(Call::StopMusic);
+        }
+
+        fn set_muted(&mut self, muted: bool) -> () {
+            self.calls.push(Call::SetMuted(muted));
+        }
+    }
+
+    impl Runnable for RecordingSound {
+        fn run_once(&mut self) -> () {}
+    }
+
+    fn calls_for(events: &[GameEvent]) -> Vec<Call> {
+        let mut director = SoundDirector::new(RecordingSound::default());
+        for event in events {
+            director.on_event(*event);
+        }
+        director.sound.calls
+    }
+
+    fn mode_changed(from: OperationMode, to: OperationMode) -> GameEvent {
+        GameEvent::ModeChanged { from, to }
+    }
+
+    #[test]
+    fn game_events_play_their_effects() {
+        let effects = [
+            (GameEvent::AppleEaten,                                SoundEffect::AppleEaten),
+            (GameEvent::ItemCollected { item: Item::Ghost },       SoundEffect::ItemCollected),
+            (GameEvent::GraceUsed,                                 SoundEffect::GraceWarning),
+            (GameEvent::Died { cause: DeathCause::Wall },          SoundEffect::Death),
+            (GameEvent::Won,                                       SoundEffect::Win),
+            (GameEvent::TimeUp,                                    SoundEffect::Win),
+            (GameEvent::MapChanged { index: 1 },                   SoundEffect::MenuClick),
+            (GameEvent::MenuNavigated,                             SoundEffect::MenuClick),
+            (mode_changed(OperationMode::Menu, OperationMode::Settings), SoundEffect::MenuClick),
+        ];
+        for &(event, effect) in effects.iter() {
+            assert!(calls_for(&[event]) == [Call::Play(effect)]);
+        }
+
+        let silent = [
+            GameEvent::GameStarted { map_index: 0, speed: GameSpeed::Normal },
+            GameEvent::GameEnded { longest_snake: 3 },
+            GameEvent::SpeedChanged { speed: GameSpeed::Fast },
+            mode_changed(OperationMode::Boot, OperationMode::Menu),
+            mode_changed(OperationMode::GameOver, OperationMode::Menu),
+        ];
+        for event in silent.iter() {
+            assert!(calls_for(&[*event]).is_empty());
+        }
+    }
+
+    #[test]
+    fn music_follows_the_game() {
+        let menu_tempo = Tune::Menu.get_melody().tempo;
+
+        assert!(calls_for(&[GameEvent::WokeUp]) == [Call::PlayMusic(Tune::Menu, menu_tempo)]);
+        assert!(calls_for(&[GameEvent::WentToSleep]) == [Call::StopMusic]);
+        assert!(calls_for(&[mode_changed(OperationMode::Playing, OperationMode::Paused)]) ==
+            [Call::StopMusic, Call::Play(SoundEffect::MenuClick)]);
+        assert!(calls_for(&[mode_changed(OperationMode::Playing, OperationMode::GameOver)]) ==
+            [Call::PlayMusic(Tune::Menu, menu_tempo)]);
+    }
+
+    #[test]
+    fn gameplay_tempo_follows_the_game_speed() {
+        let calls = calls_for(&[
+            GameEvent::SpeedChanged { speed: GameSpeed::Fast },
+            mode_changed(OperationMode::Countdown, OperationMode::Playing),
+        ]);
+        let tempo = Tune::Gameplay.get_tempo(GameSpeed::Fast);
+        assert!(tempo > Tune::Gameplay.get_melody().tempo);
+        assert!(calls == [Call::PlayMusic(Tune::Gameplay, tempo)]);
+    }
+
+    #[test]
+    fn muting_only_follows_changes_of_the_sound_setting() {
+        let sound_on = Settings::default();
+        let mut sound_off = Settings::default();
+        sound_off.set(Setting::Sound, 0);
+
+        let calls = calls_for(&[
+            GameEvent::SettingsChanged { settings: sound_on },
+            GameEvent::SettingsChanged { settings: sound_off },
+            GameEvent::SettingsChanged { settings: sound_off },
+            GameEvent::SettingsChanged { settings: sound_on },
+        ]);
+        assert!(calls == [Call::SetMuted(true), Call::SetMuted(false)]);
+    }
+}
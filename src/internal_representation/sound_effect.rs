@@ -0,0 +1,48 @@
+// Sound effects are short sequences of tones played by the buzzer.
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum SoundEffect {
+    AppleEaten,
+    ItemCollected,
+    GraceWarning,
+    Death,
+    Win,
+    MenuClick,
+}
+
+// A frequency of zero is a pause.
+#[derive(Copy, Clone)]
+pub struct Tone {
+    pub frequency: u16, // Hz
+    pub duration: u16,  // miliseconds
+}
+
+impl SoundEffect {
+    pub fn get_tones(&self) -> &'static [Tone] {
+        match self {
+            SoundEffect::AppleEaten    => &APPLE_CHIRP,
+            SoundEffect::ItemCollected => &ITEM_ARPEGGIO,
+            SoundEffect::GraceWarning  => &GRACE_BEEP,
+            SoundEffect::Death         => &DEATH_SOUND,
+            SoundEffect::Win           => &WIN_JINGLE,
+            SoundEffect::MenuClick     => &MENU_CLICK,
+        }
+    }
+}
+
+const fn tone(frequency: u16, duration: u16) -> Tone {
+    Tone { frequency, duration }
+}
+
+const APPLE_CHIRP: [Tone; 2] = [tone(1319, 40), tone(1760, 60)];
+
+const ITEM_ARPEGGIO: [Tone; 3] = [tone(988, 40), tone(1319, 40), tone(1976, 80)];
+
+const GRACE_BEEP: [Tone; 3] = [tone(880, 60), tone(0, 40), tone(880, 60)];
+
+const DEATH_SOUND: [Tone; 3] = [tone(523, 120), tone(392, 120), tone(262, 300)];
+
+const WIN_JINGLE: [Tone; 5] =
+    [tone(523, 100), tone(659, 100), tone(784, 100), tone(0, 50), tone(1047, 300)];
+
+const MENU_CLICK: [Tone; 1] = [tone(2093, 15)];
@@ -0,0 +1,211 @@
+// The state machine of the console. The modes of operation only change in
+// response to triggers, according to the transition table below. Anything
+// that isn't listed in the table is ignored, e.g. pausing while in the menu.
+
+use super::button::ButtonEvent;
+use super::direction::Direction;
+use super::game_state::OperationMode;
+use super::game_state::OperationMode::*;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Trigger {
+    BootFinished,
+    Start,
+    StartTimeAttack,
+    StartChallenge,
+    Back,
+    OpenMapSelect,
+    OpenCodeEntry,
+    OpenHighScores,
+    OpenSettings,
+    OpenStatistics,
+    CountdownFinished,
+    Pause,
+    Resume,
+    Quit,
+    Died,
+    Won,
+    TimeUp,
+    Timeout,
+}
+
+const TRANSITIONS: [(OperationMode, Trigger, OperationMode); 25] = [
+    (Boot,       Trigger::BootFinished,      Menu),
+    (Menu,       Trigger::Start,             Countdown),
+    (Menu,       Trigger::StartTimeAttack,   Countdown),
+    (Menu,       Trigger::OpenMapSelect,     MapSelect),
+    (Menu,       Trigger::OpenHighScores,    HighScores),
+    (Menu,       Trigger::OpenSettings,      Settings),
+    (MapSelect,  Trigger::Start,             Countdown),
+    (MapSelect,  Trigger::OpenCodeEntry,     CodeEntry),
+    (MapSelect,  Trigger::Back,              Menu),
+    (CodeEntry,  Trigger::StartChallenge,    Countdown),
+    (CodeEntry,  Trigger::Back,              MapSelect),
+    (Countdown,  Trigger::CountdownFinished, Playing),
+    (Playing,    Trigger::Pause,             Paused),
+    (Playing,    Trigger::Quit,              GameOver),
+    (Playing,    Trigger::Died,              GameOver),
+    (Playing,    Trigger::Won,               GameOver),
+    (Playing,    Trigger::TimeUp,            GameOver),
+    (Paused,     Trigger::Resume,            Playing),
+    (Paused,     Trigger::Quit,              GameOver),
+    (GameOver,   Trigger::Timeout,           Menu),
+    (GameOver,   Trigger::Back,              Menu),
+    (HighScores, Trigger::OpenStatistics,    Statistics),
+    (HighScores, Trigger::Back,              Menu),
+    (Settings,   Trigger::Back,              Menu),
+    (Statistics, Trigger::Back,              HighScores),
+];
+
+// The buttons are bound to triggers depending on the mode.
+const BUTTON_BINDINGS: [(OperationMode, ButtonEvent, Trigger); 16] = [
+    (Menu,       ButtonEvent::ShortClick,  Trigger::Start),
+    (Menu,       ButtonEvent::DoubleClick, Trigger::OpenHighScores),
+    (Menu,       ButtonEvent::LongPress,   Trigger::OpenSettings),
+    (MapSelect,  ButtonEvent::ShortClick,  Trigger::Start),
+    (MapSelect,  ButtonEvent::DoubleClick, Trigger::OpenCodeEntry),
+    (MapSelect,  ButtonEvent::LongPress,   Trigger::Back),
+    (CodeEntry,  ButtonEvent::LongPress,   Trigger::Back),
+    (Playing,    ButtonEvent::ShortClick,  Trigger::Pause),
+    (Playing,    ButtonEvent::LongPress,   Trigger::Quit),
+    (Paused,     ButtonEvent::ShortClick,  Trigger::Resume),
+    (Paused,     ButtonEvent::LongPress,   Trigger::Quit),
+    (GameOver,   ButtonEvent::ShortClick,  Trigger::Back),
+    (HighScores, ButtonEvent::ShortClick,  Trigger::Back),
+    (HighScores, ButtonEvent::DoubleClick, Trigger::OpenStatistics),
+    (Settings,   ButtonEvent::LongPress,   Trigger::Back),
+    (Statistics, ButtonEvent::ShortClick,  Trigger::Back),
+];
+
+// Each screen of the main menu sits in one direction, pushing the stick the
+// opposite way leads back. The statistics sit to the right of the high
+// scores and the challenge code to the left of the maps. The remaining
+// directions are interpreted by the modes themselves, e.g. to browse the maps.
+const DIRECTION_BINDINGS: [(OperationMode, Direction, Trigger); 10] = [
+    (Menu,       Direction::Left,  Trigger::OpenMapSelect),
+    (Menu,       Direction::Right, Trigger::OpenSettings),
+    (Menu,       Direction::Up,    Trigger::OpenHighScores),
+    (Menu,       Direction::Down,  Trigger::StartTimeAttack),
+    (MapSelect,  Direction::Left,  Trigger::OpenCodeEntry),
+    (MapSelect,  Direction::Right, Trigger::Back),
+    (Settings,   Direction::Left,  Trigger::Back),
+    (HighScores, Direction::Down,  Trigger::Back),
+    (HighScores, Direction::Right, Trigger::OpenStatistics),
+    (Statistics, Direction::Left,  Trigger::Back),
+];
+
+// Returns the mode the trigger leads to, None if it doesn't apply in the
+// current mode.
+pub fn next_mode(mode: OperationMode, trigger: Trigger) -> Option<OperationMode> {
+    TRANSITIONS.iter()
+        .find(|(from, on, _)| *from == mode && *on == trigger)
+        .map(|(_, _, to)| *to)
+}
+
+pub fn button_trigger(mode: OperationMode, button: ButtonEvent) -> Option<Trigger> {
+    BUTTON_BINDINGS.iter()
+        .find(|(bound_mode, event, _)| *bound_mode == mode && *event == button)
+        .map(|(_, _, trigger)| *trigger)
+}
+
+pub fn direction_trigger(mode: OperationMode, direction: Direction) -> Option<Trigger> {
+    DIRECTION_BINDINGS.iter()
+        .find(|(bound_mode, bound_direction, _)| *bound_mode == mode && *bound_direction == direction)
+        .map(|(_, _, trigger)| *trigger)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODES: [OperationMode; 11] = [
+        Boot, Menu, MapSelect, Countdown, Playing, Paused,
+        GameOver, HighScores, Settings, Statistics, CodeEntry,
+    ];
+
+    const TRIGGERS: [Trigger; 18] = [
+        Trigger::BootFinished, Trigger::Start, Trigger::StartTimeAttack,
+        Trigger::StartChallenge, Trigger::Back, Trigger::OpenMapSelect,
+        Trigger::OpenCodeEntry, Trigger::OpenHighScores, Trigger::OpenSettings,
+        Trigger::OpenStatistics, Trigger::CountdownFinished, Trigger::Pause,
+        Trigger::Resume, Trigger::Quit, Trigger::Died, Trigger::Won,
+        Trigger::TimeUp, Trigger::Timeout,
+    ];
+
+    const BUTTONS: [ButtonEvent; 5] = [
+        ButtonEvent::Press, ButtonEvent::Release, ButtonEvent::ShortClick,
+        ButtonEvent::LongPress, ButtonEvent::DoubleClick,
+    ];
+
+    const DIRECTIONS: [Direction; 5] = [
+        Direction::Up, Direction::Down, Direction::Left, Direction::Right, Direction::NoDirection,
+    ];
+
+    // The number of rows which start with the given pair.
+    fn count_rows<A: PartialEq, B: PartialEq, C>(table: &[(A, B, C)], a: A, b: B) -> usize {
+        table.iter().filter(|(first, second, _)| *first == a && *second == b).count()
+    }
+
+    #[test]
+    fn every_transition_is_reachable() {
+        for &(from, trigger, to) in TRANSITIONS.iter() {
+            assert!(next_mode(from, trigger) == Some(to));
+        }
+    }
+
+    #[test]
+    fn unlisted_triggers_are_ignored() {
+        for &mode in MODES.iter() {
+            for &trigger in TRIGGERS.iter() {
+                if count_rows(&TRANSITIONS, mode, trigger) == 0 {
+                    assert!(next_mode(mode, trigger) == None);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn each_input_is_bound_once_per_mode() {
+        for &mode in MODES.iter() {
+            for &trigger in TRIGGERS.iter() {
+                assert!(count_rows(&TRANSITIONS, mode, trigger) <= 1);
+            }
+            for &button in BUTTONS.iter() {
+                assert!(count_rows(&BUTTON_BINDINGS, mode, button) <= 1);
+            }
+            for &direction in DIRECTIONS.iter() {
+                assert!(count_rows(&DIRECTION_BINDINGS, mode, direction) <= 1);
+            }
+        }
+    }
+
+    #[test]
+    fn bound_inputs_lead_somewhere() {
+        for &(mode, button, trigger) in BUTTON_BINDINGS.iter() {
+            assert!(button_trigger(mode, button) == Some(trigger));
+            assert!(next_mode(mode, trigger).is_some());
+        }
+        for &(mode, direction, trigger) in DIRECTION_BINDINGS.iter() {
+            assert!(direction_trigger(mode, direction) == Some(trigger));
+            assert!(next_mode(mode, trigger).is_some());
+        }
+    }
+
+    #[test]
+    fn unbound_inputs_fire_nothing() {
+        for &mode in MODES.iter() {
+            for &button in BUTTONS.iter() {
+                if count_rows(&BUTTON_BINDINGS, mode, button) == 0 {
+                    assert!(button_trigger(mode, button) == None);
+                }
+            }
+            for &direction in DIRECTIONS.iter() {
+                if count_rows(&DIRECTION_BINDINGS, mode, direction) == 0 {
+                    assert!(direction_trigger(mode, direction) == None);
+                }
+            }
+        }
+        assert!(button_trigger(Playing, ButtonEvent::DoubleClick) == None);
+        assert!(direction_trigger(Playing, Direction::Up) == None);
+    }
+}
@@ -0,0 +1,341 @@
+// Lifetime statistics of the console. They are gathered from the game events
+// by the recorder below, which also keeps them in the storage, and shown by
+// the engine in the statistics screen.
+
+use core::cell::RefCell;
+
+use crate::common::STATISTICS_ADDRESS;
+use crate::libs::time::{Instant, Duration};
+use crate::libs::time_util::now;
+use crate::traits::{Storage, GameObserver, Runnable};
+use crate::user_interface::MAPS_NUMBER;
+
+use super::game_event::{GameEvent, DeathCause};
+use super::game_state::{GameSpeed, OperationMode};
+use super::item::Item;
+use super::record::{load_record, save_record};
+
+const STATISTICS_VERSION: u8 = 1;
+const SPEEDS_NUMBER: usize = 3;
+const DEATH_CAUSES_NUMBER: usize = 3;
+
+// The games played on each map at each speed, the apples, the longest snake,
+// the play time, the deaths by cause and the wins.
+const STATISTICS_SIZE: usize = MAPS_NUMBER * SPEEDS_NUMBER * 2 + 4 + 1 + 4 + DEATH_CAUSES_NUMBER * 2 + 2;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Statistic {
+    GamesPlayed,
+    MapGamesPlayed, // On the selected map at the current speed.
+    Apples,
+    LongestSnake,
+    PlayTime,       // In minutes.
+    WallDeaths,
+    ObstacleDeaths,
+    SnakeDeaths,
+    Wins,
+}
+
+// The order in which the statistics are browsed.
+pub const STATISTICS: [Statistic; 9] = [
+    Statistic::GamesPlayed,
+    Statistic::MapGamesPlayed,
+    Statistic::Apples,
+    Statistic::LongestSnake,
+    Statistic::PlayTime,
+    Statistic::WallDeaths,
+    Statistic::ObstacleDeaths,
+    Statistic::SnakeDeaths,
+    Statistic::Wins,
+];
+
+#[derive(Default)]
+pub struct Statistics {
+    games_played: [[u16; SPEEDS_NUMBER]; MAPS_NUMBER],
+    apples: u32,
+    longest_snake: u8,
+    play_time: u32, // In seconds.
+    deaths: [u16; DEATH_CAUSES_NUMBER],
+    wins: u16,
+}
+
+impl Statistics {
+    pub fn load(storage: &dyn Storage) -> Option<Statistics> {
+        let mut data = [0; STATISTICS_SIZE];
+        if !load_record(storage, STATISTICS_ADDRESS, STATISTICS_VERSION, &mut data) {
+            return None;
+        }
+
+        let mut reader = ByteReader { data: &data, position: 0 };
+        let mut statistics = Statistics::default();
+        for games in statistics.games_played.iter_mut().flat_map(|map| map.iter_mut()) {
+            *games = reader.read_u16();
+        }
+        statistics.apples = reader.read_u32();
+        statistics.longest_snake = reader.read_u8();
+        statistics.play_time = reader.read_u32();
+        for deaths in statistics.deaths.iter_mut() {
+            *deaths = reader.read_u16();
+        }
+        statistics.wins = reader.read_u16();
+        Some(statistics)
+    }
+
+    pub fn save(&self, storage: &mut dyn Storage) {
+        let mut data = [0; STATISTICS_SIZE];
+        let mut writer = ByteWriter { data: &mut data, position: 0 };
+        for games in self.games_played.iter().flat_map(|map| map.iter()) {
+            writer.write_u16(*games);
+        }
+        writer.write_u32(self.apples);
+        writer.write_u8(self.longest_snake);
+        writer.write_u32(self.play_time);
+        for deaths in self.deaths.iter() {
+            writer.write_u16(*deaths);
+        }
+        writer.write_u16(self.wins);
+
+        save_record(storage, STATISTICS_ADDRESS, STATISTICS_VERSION, &data);
+    }
+
+    pub fn value(&self, statistic: Statistic, map_index: usize, speed: GameSpeed) -> u32 {
+        match statistic {
+            Statistic::GamesPlayed    => {
+                self.games_played.iter().flat_map(|map| map.iter()).map(|games| *games as u32).sum()
+            },
+            Statistic::MapGamesPlayed => self.games_played[map_index][speed as usize] as u32,
+            Statistic::Apples         => self.apples,
+            Statistic::LongestSnake   => self.longest_snake as u32,
+            Statistic::PlayTime       => self.play_time / 60,
+            Statistic::WallDeaths     => self.deaths[DeathCause::Wall as usize] as u32,
+            Statistic::ObstacleDeaths => self.deaths[DeathCause::Obstacle as usize] as u32,
+            Statistic::SnakeDeaths    => self.deaths[DeathCause::Snake as usize] as u32,
+            Statistic::Wins           => self.wins as u32,
+        }
+    }
+}
+
+// Updates the statistics as the games go and saves them once each game ends,
+// which keeps the writes to the EEPROM to a minimum.
+pub struct StatisticsRecorder<'a, S: Storage> {
+    statistics: &'a RefCell<Statistics>,
+    storage: S,
+    playing_since: Option<Instant>,
+    // The play time which doesn't add up to a whole second yet.
+    unrecorded_play_time: Duration,
+}
+
+impl<'a, S: Storage> StatisticsRecorder<'a, S> {
+    pub fn new(statistics: &'a RefCell<Statistics>, storage: S) -> Self {
+        StatisticsRecorder {
+            statistics,
+            storage,
+            playing_since: None,
+            unrecorded_play_time: Duration::ZERO,
+        }
+    }
+
+    fn on_mode_changed(&mut self, from: OperationMode, to: OperationMode) {
+        if to == OperationMode::Playing {
+            self.playing_since = Some(now());
+        }
+
+        if from == OperationMode::Playing {
+            if let Some(playing_since) = self.playing_since.take() {
+                let play_time = self.unrecorded_play_time + (now() - playing_since);
+                let seconds = play_time.as_millis() / 1000;
+                self.unrecorded_play_time = play_time - Duration::from_secs(seconds);
+
+                let mut statistics = self.statistics.borrow_mut();
+                statistics.play_time = statistics.play_time.saturating_add(seconds);
+            }
+        }
+    }
+}
+
+impl<'a, S: Storage> GameObserver for StatisticsRecorder<'a, S> {
+    fn on_event(&mut self, event: GameEvent) -> () {
+        if let GameEvent::ModeChanged { from, to } = event {
+            return self.on_mode_changed(from, to);
+        }
+
+        let mut statistics = self.statistics.borrow_mut();
+        match event {
+            GameEvent::GameStarted { map_index, speed } => {
+                let games = &mut statistics.games_played[map_index][speed as usize];
+                *games = games.saturating_add(1);
+            },
+            GameEvent::AppleEaten                                   |
+            GameEvent::ItemCollected { item: Item::GoldenApple }    => {
+                statistics.apples = statistics.apples.saturating_add(1);
+            },
+            GameEvent::Died { cause } => {
+                let deaths = &mut statistics.deaths[cause as usize];
+                *deaths = deaths.saturating_add(1);
+            },
+            GameEvent::Won => statistics.wins = statistics.wins.saturating_add(1),
+            GameEvent::GameEnded { longest_snake } => {
+                statistics.longest_snake = statistics.longest_snake.max(longest_snake);
+                statistics.save(&mut self.storage);
+            },
+            _ => (),
+        }
+    }
+}
+
+impl<'a, S: Storage> Runnable for StatisticsRecorder<'a, S> {
+    fn run_once(&mut self) -> () {}
+}
+
+struct ByteReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn read_u8(&mut self) -> u8 {
+        let value = self.data[self.position];
+        self.position += 1;
+        value
+    }
+
+    fn read_u16(&mut self) -> u16 {
+        u16::from_le_bytes([self.read_u8(), self.read_u8()])
+    }
+
+    fn read_u32(&mut self) -> u32 {
+        u32::from_le_bytes([self.read_u8(), self.read_u8(), self.read_u8(), self.read_u8()])
+    }
+}
+
+struct ByteWriter<'a> {
+    data: &'a mut [u8],
+    position: usize,
+}
+
+impl<'a> ByteWriter<'a> {
+    fn write_u8(&mut self, value: u8) {
+        self.data[self.position] = value;
+        self.position += 1;
+    }
+
+    fn write_u16(&mut self, value: u16) {
+        for byte in value.to_le_bytes().iter() {
+            self.write_u8(*byte);
+        }
+    }
+
+    fn write_u32(&mut self, value: u32) {
+        for byte in value.to_le_bytes().iter() {
+            self.write_u8(*byte);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::libs::time_util::{set_now, advance};
+    use crate::internal_representation::record::FakeStorage;
+
+    fn mode_changed(from: OperationMode, to: OperationMode) -> GameEvent {
+        GameEvent::ModeChanged { from, to }
+    }
+
+    #[test]
+    fn statistics_survive_a_round_trip_through_the_storage() {
+        let mut statistics = Statistics::default();
+        statistics.games_played[0][0] = 1;
+        statistics.games_played[MAPS_NUMBER - 1][SPEEDS_NUMBER - 1] = 0x0302;
+        statistics.apples = 0x0706_0504;
+        statistics.longest_snake = 42;
+        statistics.play_time = 0x0b0a_0908;
+        statistics.deaths = [0x0d0c, 0x0f0e, 0x1110];
+        statistics.wins = 0xabcd;
+
+        let mut storage = FakeStorage::new();
+        assert!(Statistics::load(&storage).is_none());
+        statistics.save(&mut storage);
+
+        // The wins are the last data of the record, right before the checksum.
+        let last_byte = STATISTICS_ADDRESS as usize + STATISTICS_SIZE;
+        assert!(storage.bytes[last_byte] == 0xab);
+
+        let loaded = Statistics::load(&storage).unwrap();
+        assert!(loaded.games_played == statistics.games_played);
+        assert!(loaded.apples == statistics.apples);
+        assert!(loaded.longest_snake == statistics.longest_snake);
+        assert!(loaded.play_time == statistics.play_time);
+        assert!(loaded.deaths == statistics.deaths);
+        assert!(loaded.wins == statistics.wins);
+    }
+
+    #[test]
+    fn games_are_counted_by_map_and_speed() {
+        let statistics = RefCell::new(Statistics::default());
+        let mut recorder = StatisticsRecorder::new(&statistics, FakeStorage::new());
+
+        recorder.on_event(GameEvent::GameStarted { map_index: 3, speed: GameSpeed::Fast });
+        recorder.on_event(GameEvent::GameStarted { map_index: 3, speed: GameSpeed::Fast });
+        recorder.on_event(GameEvent::GameStarted { map_index: 3, speed: GameSpeed::Slow });
+
+        let statistics = statistics.borrow();
+        assert!(statistics.value(Statistic::MapGamesPlayed, 3, GameSpeed::Fast) == 2);
+        assert!(statistics.value(Statistic::MapGamesPlayed, 3, GameSpeed::Slow) == 1);
+        assert!(statistics.value(Statistic::MapGamesPlayed, 0, GameSpeed::Fast) == 0);
+        assert!(statistics.value(Statistic::GamesPlayed, 0, GameSpeed::Normal) == 3);
+    }
+
+    #[test]
+    fn deaths_are_counted_by_cause() {
+        let statistics = RefCell::new(Statistics::default());
+        let mut recorder = StatisticsRecorder::new(&statistics, FakeStorage::new());
+
+        recorder.on_event(GameEvent::Died { cause: DeathCause::Wall });
+        recorder.on_event(GameEvent::Died { cause: DeathCause::Snake });
+        recorder.on_event(GameEvent::Died { cause: DeathCause::Snake });
+
+        let statistics = statistics.borrow();
+        let value = |statistic| statistics.value(statistic, 0, GameSpeed::Normal);
+        assert!(value(Statistic::WallDeaths) == 1);
+        assert!(value(Statistic::ObstacleDeaths) == 0);
+        assert!(value(Statistic::SnakeDeaths) == 2);
+    }
+
+    #[test]
+    fn play_time_leaves_out_the_pauses() {
+        set_now(Instant::from_millis(1000));
+        let statistics = RefCell::new(Statistics::default());
+        let mut recorder = StatisticsRecorder::new(&statistics, FakeStorage::new());
+
+        recorder.on_event(mode_changed(OperationMode::Countdown, OperationMode::Playing));
+        advance(Duration::from_millis(1500));
+        recorder.on_event(mode_changed(OperationMode::Playing, OperationMode::Paused));
+        assert!(statistics.borrow().play_time == 1);
+
+        // The half second left over is carried on to the next time.
+        advance(Duration::from_secs(10));
+        recorder.on_event(mode_changed(OperationMode::Paused, OperationMode::Playing));
+        advance(Duration::from_millis(1700));
+        recorder.on_event(mode_changed(OperationMode::Playing, OperationMode::GameOver));
+        assert!(statistics.borrow().play_time == 3);
+    }
+
+    #[test]
+    fn statistics_are_saved_when_a_game_ends() {
+        let statistics = RefCell::new(Statistics::default());
+        let mut recorder = StatisticsRecorder::new(&statistics, FakeStorage::new());
+
+        recorder.on_event(GameEvent::AppleEaten);
+        recorder.on_event(GameEvent::ItemCollected { item: Item::GoldenApple });
+        recorder.on_event(GameEvent::Won);
+        assert!(Statistics::load(&recorder.storage).is_none());
+
+        recorder.on_event(GameEvent::GameEnded { longest_snake: 12 });
+        let loaded = Statistics::load(&recorder.storage).unwrap();
+        assert!(loaded.apples == 2);
+        assert!(loaded.wins == 1);
+        assert!(loaded.longest_snake == 12);
+    }
+}
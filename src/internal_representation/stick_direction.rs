@@ -0,0 +1,81 @@
+// Turns the displacement of the analog stick into a direction, the same way
+// whatever hardware reads the stick.
+
+use crate::common::STICK_HYSTERESIS;
+
+use super::direction::Direction;
+
+// When the stick is displaced along both axes, the dominant one wins. The axis
+// of the previously registered direction is favoured by STICK_HYSTERESIS
+// percent so that diagonals don't flicker between two directions.
+pub fn determine_direction(
+    x: i16, y: i16, dead_zone: u8, previous: Direction) -> Direction {
+
+    let x_active = x.abs() > dead_zone as i16;
+    let y_active = y.abs() > dead_zone as i16;
+
+    let x_direction = if x < 0 { Direction::Up } else { Direction::Down };
+    let y_direction = if y < 0 { Direction::Right } else { Direction::Left };
+
+    match (x_active, y_active) {
+        (false, false) => Direction::NoDirection,
+        (true, false)  => x_direction,
+        (false, true)  => y_direction,
+        (true, true)   => {
+            let x_bias = if is_vertical(previous) { STICK_HYSTERESIS } else { 0 };
+            let y_bias = if is_horizontal(previous) { STICK_HYSTERESIS } else { 0 };
+
+            if x.abs() + x_bias >= y.abs() + y_bias { x_direction } else { y_direction }
+        }
+    }
+}
+
+fn is_vertical(direction: Direction) -> bool {
+    direction == Direction::Up || direction == Direction::Down
+}
+
+fn is_horizontal(direction: Direction) -> bool {
+    direction == Direction::Left || direction == Direction::Right
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEAD_ZONE: u8 = 70;
+
+    fn direction(x: i16, y: i16) -> Direction {
+        determine_direction(x, y, DEAD_ZONE, Direction::NoDirection)
+    }
+
+    #[test]
+    fn dead_zone_registers_no_direction() {
+        assert!(direction(0, 0) == Direction::NoDirection);
+        assert!(direction(-70, 70) == Direction::NoDirection);
+        assert!(determine_direction(-60, 0, 50, Direction::NoDirection) == Direction::Up);
+    }
+
+    #[test]
+    fn each_axis_has_two_directions() {
+        assert!(direction(-100, 0) == Direction::Up);
+        assert!(direction(100, 0) == Direction::Down);
+        assert!(direction(0, -100) == Direction::Right);
+        assert!(direction(0, 100) == Direction::Left);
+    }
+
+    #[test]
+    fn dominant_axis_wins_on_a_diagonal() {
+        assert!(direction(-90, 80) == Direction::Up);
+        assert!(direction(80, -90) == Direction::Right);
+        // A tie goes to the x axis.
+        assert!(direction(85, 85) == Direction::Down);
+    }
+
+    #[test]
+    fn previous_axis_is_favoured_on_a_diagonal() {
+        assert!(determine_direction(-80, -90, DEAD_ZONE, Direction::Up) == Direction::Up);
+        assert!(determine_direction(-80, -100, DEAD_ZONE, Direction::Up) == Direction::Right);
+        assert!(determine_direction(-90, -80, DEAD_ZONE, Direction::Right) == Direction::Right);
+        assert!(determine_direction(-95, -80, DEAD_ZONE, Direction::Left) == Direction::Up);
+    }
+}
@@ -0,0 +1,113 @@
+use crate::libs::ring_buffer::RingBuffer;
+
+use super::direction::{Direction, Turn};
+
+// The number of turns that can be registered between two consecutive steps of
+// the snake, e.g. a quick "up then left" to make a U-turn around a wall.
+pub const MAX_PENDING_TURNS: usize = 3;
+
+pub struct TurnQueue {
+    turns: RingBuffer<Direction, MAX_PENDING_TURNS>,
+}
+
+impl TurnQueue {
+    pub fn new() -> TurnQueue {
+        TurnQueue { turns: RingBuffer::new() }
+    }
+
+    // Each turn is validated against the direction the snake will be heading in
+    // once all turns queued before it are applied, that is the last queued
+    // turn or the current direction if the queue is empty. Repeated turns and
+    // reversals are dropped, as well as any turns once the queue is full.
+    pub fn push(&mut self, turn: Direction, current_direction: Direction) {
+        let previous = self.turns.back().unwrap_or(current_direction);
+
+        if turn == Direction::NoDirection ||
+           turn == previous ||
+           turn == Direction::get_opposite(previous) { return; }
+
+        self.turns.push_back(turn).ok();
+    }
+
+    // Relative turns are applied to the direction the snake will be heading in
+    // once all turns queued before it are applied.
+    pub fn push_relative(&mut self, turn: Turn, current_direction: Direction) {
+        let previous = self.turns.back().unwrap_or(current_direction);
+        self.push(previous.turned(turn), current_direction);
+    }
+
+    pub fn pop(&mut self) -> Option<Direction> {
+        self.turns.pop_front()
+    }
+
+    pub fn clear(&mut self) {
+        self.turns.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drain(queue: &mut TurnQueue) -> Vec<Direction> {
+        let mut turns = Vec::new();
+        while let Some(turn) = queue.pop() {
+            turns.push(turn);
+        }
+        turns
+    }
+
+    #[test]
+    fn quick_turns_between_two_steps_are_kept() {
+        let mut queue = TurnQueue::new();
+        queue.push(Direction::Up, Direction::Right);
+        queue.push(Direction::Left, Direction::Right);
+        assert!(drain(&mut queue) == [Direction::Up, Direction::Left]);
+    }
+
+    #[test]
+    fn repeated_turns_are_dropped() {
+        let mut queue = TurnQueue::new();
+        queue.push(Direction::Right, Direction::Right);
+        queue.push(Direction::Up, Direction::Right);
+        queue.push(Direction::Up, Direction::Right);
+        queue.push(Direction::NoDirection, Direction::Right);
+        assert!(drain(&mut queue) == [Direction::Up]);
+    }
+
+    #[test]
+    fn reversals_are_checked_against_the_last_queued_turn() {
+        let mut queue = TurnQueue::new();
+        queue.push(Direction::Left, Direction::Right);
+        assert!(drain(&mut queue).is_empty());
+
+        // Left reverses the direction of the snake but not the queued turn.
+        queue.push(Direction::Up, Direction::Right);
+        queue.push(Direction::Down, Direction::Right);
+        queue.push(Direction::Left, Direction::Right);
+        assert!(drain(&mut queue) == [Direction::Up, Direction::Left]);
+    }
+
+    #[test]
+    fn turns_beyond_the_capacity_are_dropped() {
+        let mut queue = TurnQueue::new();
+        queue.push(Direction::Up, Direction::Right);
+        queue.push(Direction::Left, Direction::Right);
+        queue.push(Direction::Down, Direction::Right);
+        queue.push(Direction::Right, Direction::Right);
+        assert!(drain(&mut queue) == [Direction::Up, Direction::Left, Direction::Down]);
+
+        queue.push(Direction::Up, Direction::Right);
+        queue.clear();
+        assert!(queue.pop().is_none());
+    }
+
+    #[test]
+    fn relative_turns_follow_on_from_the_queued_ones() {
+        let mut queue = TurnQueue::new();
+        queue.push_relative(Turn::Left, Direction::Right);
+        queue.push_relative(Turn::Left, Direction::Right);
+        queue.push_relative(Turn::Right, Direction::Right);
+        assert!(drain(&mut queue) == [Direction::Up, Direction::Left, Direction::Up]);
+    }
+}
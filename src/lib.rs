@@ -0,0 +1,28 @@
+// The hardware-free part of the console: the game logic, the user interface
+// and the utilities. Apart from being linked into the firmware, it builds for
+// the host so that it can be tested there (`cargo test-host`).
+
+#![cfg_attr(target_arch = "avr", no_std)]
+#![cfg_attr(target_arch = "avr", feature(abi_avr_interrupt))]
+#![cfg_attr(target_arch = "avr", feature(asm_experimental_arch))]
+#![feature(trait_upcasting)]
+#![allow(incomplete_features)]
+
+#[cfg(not(target_arch = "avr"))]
+extern crate core;
+#[cfg(target_arch = "avr")]
+extern crate arduino_hal;
+#[cfg(target_arch = "avr")]
+extern crate avr_device;
+extern crate embedded_hal;
+extern crate arrayvec;
+extern crate oorandom;
+
+pub mod traits;
+pub mod common;
+pub mod game_engine;
+pub mod user_interface;
+pub mod dpad;
+pub mod rotary_encoder;
+pub mod internal_representation;
+pub mod libs;
@@ -0,0 +1,58 @@
+//! Byte access to the internal EEPROM of the ATmega328P
+
+use crate::traits::Storage;
+
+pub const EEPROM_SIZE: u16 = 1024;
+
+// Bits of the EEPROM control register.
+const EERE: u8 = 1 << 0;
+const EEPE: u8 = 1 << 1;
+const EEMPE: u8 = 1 << 2;
+
+/// Handle to the EEPROM, it doesn't own any state so it can be freely created
+/// by all subsystems that need to persist data.
+pub struct Eeprom;
+
+impl Eeprom {
+    pub fn new() -> Eeprom {
+        Eeprom
+    }
+}
+
+impl Storage for Eeprom {
+    fn read_byte(&self, address: u16) -> u8 {
+        let eeprom = registers();
+        wait_for_write_completion();
+
+        eeprom.eear.write(|w| unsafe { w.bits(address) });
+        eeprom.eecr.write(|w| unsafe { w.bits(EERE) });
+        eeprom.eedr.read().bits()
+    }
+
+    /// Writes are skipped if the value is already stored to save on the
+    /// limited number of EEPROM write cycles.
+    fn write_byte(&mut self, address: u16, value: u8) {
+        if self.read_byte(address) == value {
+            return;
+        }
+
+        let eeprom = registers();
+        eeprom.eear.write(|w| unsafe { w.bits(address) });
+        eeprom.eedr.write(|w| unsafe { w.bits(value) });
+
+        // The write needs to be started within four clock cycles of setting
+        // the master write enable bit, hence interrupts are disabled.
+        avr_device::interrupt::free(|_| {
+            eeprom.eecr.write(|w| unsafe { w.bits(EEMPE) });
+            eeprom.eecr.write(|w| unsafe { w.bits(EEMPE | EEPE) });
+        });
+    }
+}
+
+fn wait_for_write_completion() {
+    while registers().eecr.read().bits() & EEPE != 0 {}
+}
+
+fn registers() -> &'static arduino_hal::pac::eeprom::RegisterBlock {
+    unsafe { &*arduino_hal::pac::EEPROM::ptr() }
+}
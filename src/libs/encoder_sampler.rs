@@ -0,0 +1,73 @@
+//! Sampling of the signals of a quadrature rotary encoder (PC0 and PC1)
+//!
+//! Timer1 interrupts once every millisecond and feeds the state of the signal
+//! pins to the decoder, which accumulates the detents until they are taken.
+
+use core::cell::RefCell;
+
+use arduino_hal::port::Pin;
+use arduino_hal::port::mode::{Input, PullUp};
+use arduino_hal::hal::port::{PC0, PC1};
+use avr_device::interrupt::Mutex;
+use embedded_hal::digital::v2::InputPin;
+
+use crate::internal_representation::direction::Turn;
+use crate::internal_representation::quadrature::QuadratureDecoder;
+
+use super::time_util::CPU_FREQUENCY;
+
+// Bits of the registers involved, see the ATmega328P datasheet.
+const WGM12: u8 = 1 << 3;
+const CS_PRESCALE_64: u8 = 0b_011;
+const OCIE1A: u8 = 1 << 1;
+
+const PRESCALER: u32 = 64;
+const TIMER_COUNTS: u32 = CPU_FREQUENCY / PRESCALER / 1000;
+
+struct Signals {
+    a_pin: Pin<Input<PullUp>, PC0>,
+    b_pin: Pin<Input<PullUp>, PC1>,
+    decoder: QuadratureDecoder,
+}
+
+static SIGNALS: Mutex<RefCell<Option<Signals>>> = Mutex::new(RefCell::new(None));
+
+/// Starts sampling the signal pins, which connect to the ground when active.
+pub fn sampling_init(
+        tc1: arduino_hal::pac::TC1,
+        a_pin: Pin<Input<PullUp>, PC0>,
+        b_pin: Pin<Input<PullUp>, PC1>) {
+
+    let decoder = QuadratureDecoder::new(is_low(&a_pin), is_low(&b_pin));
+    avr_device::interrupt::free(|cs| {
+        SIGNALS.borrow(cs).replace(Some(Signals { a_pin, b_pin, decoder }));
+    });
+
+    // The timer counts in CTC mode from zero up to and including the compare
+    // value.
+    tc1.tccr1a.write(|w| unsafe { w.bits(0) });
+    tc1.tccr1b.write(|w| unsafe { w.bits(WGM12 | CS_PRESCALE_64) });
+    tc1.ocr1a.write(|w| unsafe { w.bits((TIMER_COUNTS - 1) as u16) });
+    tc1.timsk1.write(|w| unsafe { w.bits(OCIE1A) });
+}
+
+/// Takes a single detent the knob was turned by since the last call.
+pub fn take_turn() -> Option<Turn> {
+    avr_device::interrupt::free(|cs| {
+        SIGNALS.borrow(cs).borrow_mut().as_mut().and_then(|signals| signals.decoder.take_turn())
+    })
+}
+
+#[avr_device::interrupt(atmega328p)]
+fn TIMER1_COMPA() {
+    avr_device::interrupt::free(|cs| {
+        if let Some(signals) = SIGNALS.borrow(cs).borrow_mut().as_mut() {
+            let (a, b) = (is_low(&signals.a_pin), is_low(&signals.b_pin));
+            signals.decoder.update(a, b);
+        }
+    })
+}
+
+fn is_low<P: InputPin>(pin: &P) -> bool {
+    pin.is_low().unwrap_or(false)
+}
@@ -0,0 +1,40 @@
+//! Fixed capacity dispatcher of game events to the observers subscribed to it
+
+use arrayvec::ArrayVec;
+
+use crate::internal_representation::game_event::GameEvent;
+use crate::traits::{GameObserver, Runnable};
+
+pub struct EventDispatcher<'a, const N: usize> {
+    observers: ArrayVec<&'a mut dyn GameObserver, N>,
+}
+
+impl<'a, const N: usize> EventDispatcher<'a, N> {
+    pub fn new() -> Self {
+        EventDispatcher { observers: ArrayVec::new() }
+    }
+
+    /// Subscribes the observer to all the events, gives it back if there is
+    /// no room left.
+    pub fn subscribe(&mut self, observer: &'a mut dyn GameObserver)
+        -> Result<(), &'a mut dyn GameObserver> {
+        self.observers.try_push(observer).map_err(|error| error.element())
+    }
+
+    /// Hands the event to the observers in the order they subscribed in.
+    pub fn dispatch(&mut self, event: GameEvent) {
+        for observer in self.observers.iter_mut() {
+            observer.on_event(event);
+        }
+    }
+}
+
+// Observers may need to keep working between the events, e.g. to move on to
+// the next note of a melody.
+impl<'a, const N: usize> Runnable for EventDispatcher<'a, N> {
+    fn run_once(&mut self) -> () {
+        for observer in self.observers.iter_mut() {
+            observer.run_once();
+        }
+    }
+}
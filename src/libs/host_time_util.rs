@@ -0,0 +1,44 @@
+//! Time keeping on the host
+//!
+//! Stands in for the Timer0 based time keeping when the library is built for
+//! the host. The time doesn't pass on its own, it is set by the tests, and
+//! each thread keeps its own so that the tests can run in parallel.
+
+use core::cell::Cell;
+
+use crate::traits::Clock;
+
+use super::time::{Instant, Duration};
+
+thread_local! {
+    static MILLIS_COUNTER: Cell<u32> = Cell::new(0);
+}
+
+pub fn now() -> Instant {
+    Instant::from_millis(MILLIS_COUNTER.with(|counter| counter.get()))
+}
+
+/// Moves the time of the current thread to `instant`.
+pub fn set_now(instant: Instant) {
+    MILLIS_COUNTER.with(|counter| counter.set(instant.as_millis()));
+}
+
+/// Lets `duration` pass on the clock of the current thread.
+pub fn advance(duration: Duration) {
+    set_now(now() + duration);
+}
+
+// The clock of the current thread, for the code which is generic over clocks.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        now()
+    }
+}
+
+/// Returns the number of microseconds since the time started, with
+/// millisecond resolution.
+pub fn micros() -> u32 {
+    now().as_millis().wrapping_mul(1000)
+}
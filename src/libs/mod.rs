@@ -1,3 +1,20 @@
+#[cfg(target_arch = "avr")]
 #[allow(warnings, unused)]
 pub mod shift_register;
+pub mod time;
+#[cfg_attr(not(target_arch = "avr"), path = "host_time_util.rs")]
 pub mod time_util;
+pub mod ring_buffer;
+pub mod scheduler;
+pub mod event_dispatcher;
+#[cfg(target_arch = "avr")]
+pub mod eeprom;
+#[cfg(target_arch = "avr")]
+pub mod tone;
+pub mod progmem;
+#[cfg(target_arch = "avr")]
+pub mod sleep;
+#[cfg(target_arch = "avr")]
+pub mod watchdog;
+#[cfg(all(target_arch = "avr", feature = "rotary-encoder"))]
+pub mod encoder_sampler;
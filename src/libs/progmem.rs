@@ -0,0 +1,30 @@
+//! Access to data stored in the program memory (flash)
+//!
+//! Statics placed in the `.progmem.data` section stay in flash instead of being
+//! copied into the SRAM on startup. Their addresses point into the program
+//! memory, so they must never be dereferenced and are read with `lpm` instead.
+//! On the host there is a single address space and they are read as usual.
+
+/// Reads the byte at `index` of a slice placed in the program memory.
+pub fn read_byte(bytes: &'static [u8], index: usize) -> u8 {
+    if index >= bytes.len() {
+        return 0;
+    }
+
+    read_unchecked(bytes, index)
+}
+
+#[cfg(target_arch = "avr")]
+fn read_unchecked(bytes: &'static [u8], index: usize) -> u8 {
+    let value: u8;
+    unsafe {
+        let address = bytes.as_ptr().add(index);
+        core::arch::asm!("lpm {}, Z", out(reg) value, in("Z") address);
+    }
+    value
+}
+
+#[cfg(not(target_arch = "avr"))]
+fn read_unchecked(bytes: &'static [u8], index: usize) -> u8 {
+    bytes[index]
+}
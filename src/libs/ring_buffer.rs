@@ -0,0 +1,196 @@
+//! Fixed capacity ring buffer with O(1) insertion at the back and removal from
+//! the front.
+
+use core::mem::MaybeUninit;
+
+pub struct RingBuffer<T, const N: usize>
+    where T: Copy
+{
+    items: [MaybeUninit<T>; N],
+    front: usize,
+    len: usize,
+}
+
+impl<T, const N: usize> RingBuffer<T, N>
+    where T: Copy
+{
+    pub fn new() -> Self {
+        RingBuffer { items: [MaybeUninit::uninit(); N], front: 0, len: 0 }
+    }
+
+    /// Appends an item at the back, gives it back if the buffer is full.
+    pub fn push_back(&mut self, item: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(item);
+        }
+        self.items[self.physical_index(self.len)] = MaybeUninit::new(item);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Removes the item at the front, that is the oldest one.
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        let item = self.get(0);
+        self.front = (self.front + 1) % N;
+        self.len -= 1;
+        item
+    }
+
+    pub fn front(&self) -> Option<T> {
+        self.get(0)
+    }
+
+    pub fn back(&self) -> Option<T> {
+        match self.len {
+            0 => None,
+            len => self.get(len - 1),
+        }
+    }
+
+    // Returns the item at `index` counting from the front.
+    fn get(&self, index: usize) -> Option<T> {
+        if index < self.len {
+            // Only the items within the length of the buffer are initialised.
+            Some(unsafe { self.items[self.physical_index(index)].assume_init() })
+        } else {
+            None
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    pub fn clear(&mut self) {
+        self.front = 0;
+        self.len = 0;
+    }
+
+    /// Iterates over the items from the front to the back, use `rev` to
+    /// iterate from the back.
+    pub fn iter(&self) -> Iter<T, N> {
+        Iter { buffer: self, front: 0, back: self.len }
+    }
+
+    fn physical_index(&self, index: usize) -> usize {
+        (self.front + index) % N
+    }
+}
+
+pub struct Iter<'a, T, const N: usize>
+    where T: Copy
+{
+    buffer: &'a RingBuffer<T, N>,
+    front: usize,
+    back: usize,
+}
+
+impl<'a, T, const N: usize> Iterator for Iter<'a, T, N>
+    where T: Copy
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.front == self.back {
+            return None;
+        }
+        self.front += 1;
+        self.buffer.get(self.front - 1)
+    }
+}
+
+impl<'a, T, const N: usize> DoubleEndedIterator for Iter<'a, T, N>
+    where T: Copy
+{
+    fn next_back(&mut self) -> Option<T> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        self.buffer.get(self.back)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn items_come_out_in_order_across_wraparound() {
+        let mut buffer: RingBuffer<u8, 3> = RingBuffer::new();
+        for item in 0..10 {
+            assert!(buffer.push_back(item) == Ok(()));
+            assert!(buffer.pop_front() == Some(item));
+        }
+        assert!(buffer.pop_front() == None);
+
+        for item in 10..13 {
+            buffer.push_back(item).unwrap();
+        }
+        assert!(buffer.front() == Some(10));
+        assert!(buffer.back() == Some(12));
+        assert!(buffer.iter().collect::<Vec<_>>() == [10, 11, 12]);
+    }
+
+    #[test]
+    fn full_buffer_gives_item_back() {
+        let mut buffer: RingBuffer<u8, 2> = RingBuffer::new();
+        assert!(buffer.is_empty() && !buffer.is_full());
+
+        buffer.push_back(1).unwrap();
+        buffer.push_back(2).unwrap();
+        assert!(buffer.is_full() && buffer.len() == 2);
+        assert!(buffer.push_back(3) == Err(3));
+
+        buffer.pop_front();
+        assert!(!buffer.is_full());
+        assert!(buffer.push_back(3) == Ok(()));
+        assert!(buffer.iter().collect::<Vec<_>>() == [2, 3]);
+    }
+
+    #[test]
+    fn iterates_in_both_directions() {
+        let mut buffer: RingBuffer<u8, 4> = RingBuffer::new();
+        for item in 0..6 {
+            if buffer.is_full() {
+                buffer.pop_front();
+            }
+            buffer.push_back(item).unwrap();
+        }
+
+        assert!(buffer.iter().collect::<Vec<_>>() == [2, 3, 4, 5]);
+        assert!(buffer.iter().rev().collect::<Vec<_>>() == [5, 4, 3, 2]);
+
+        let mut iter = buffer.iter();
+        assert!(iter.next() == Some(2));
+        assert!(iter.next_back() == Some(5));
+        assert!(iter.next() == Some(3));
+        assert!(iter.next_back() == Some(4));
+        assert!(iter.next() == None && iter.next_back() == None);
+    }
+
+    #[test]
+    fn get_out_of_range_is_none() {
+        let mut buffer: RingBuffer<u8, 4> = RingBuffer::new();
+        assert!(buffer.get(0) == None);
+
+        buffer.push_back(7).unwrap();
+        assert!(buffer.get(0) == Some(7));
+        assert!(buffer.get(1) == None);
+        assert!(buffer.get(4) == None);
+
+        buffer.clear();
+        assert!(buffer.get(0) == None && buffer.front() == None && buffer.back() == None);
+    }
+}
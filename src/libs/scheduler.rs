@@ -0,0 +1,233 @@
+//! Cooperative scheduler of periodic tasks
+//!
+//! Tasks are registered with a period and a priority. The scheduler doesn't
+//! run anything itself, instead `next_due` hands out the identifier of the
+//! task which should run now and the caller dispatches it. This way the
+//! tasks can borrow whatever they need in the main loop.
+
+use arrayvec::ArrayVec;
+
+use crate::libs::time::{Instant, Duration};
+use crate::traits::Clock;
+
+#[derive(Copy, Clone)]
+struct ScheduledTask<T>
+    where T: Copy + PartialEq
+{
+    task: T,
+    period: Duration,
+    priority: u8,
+    next_run: Instant,
+    missed_deadlines: u16,
+}
+
+pub struct Scheduler<T, C, const N: usize>
+    where T: Copy + PartialEq, C: Clock
+{
+    clock: C,
+    tasks: ArrayVec<ScheduledTask<T>, N>,
+}
+
+impl<T, C, const N: usize> Scheduler<T, C, N>
+    where T: Copy + PartialEq, C: Clock
+{
+    pub fn new(clock: C) -> Self {
+        Scheduler { clock, tasks: ArrayVec::new() }
+    }
+
+    /// Registers a task which is due straight away and then once per period.
+    /// When several tasks are due, the one with the highest priority runs
+    /// first, ties are resolved in the order of registration. Gives the task
+    /// back if there is no room left.
+    pub fn register(&mut self, task: T, period: Duration, priority: u8) -> Result<(), T> {
+        let next_run = self.clock.now();
+        self.tasks.try_push(ScheduledTask {
+            task,
+            period,
+            priority,
+            next_run,
+            missed_deadlines: 0,
+        }).map_err(|error| error.element().task)
+    }
+
+    /// Returns the task which should run now and schedules its next run, or
+    /// None if no task is due yet.
+    pub fn next_due(&mut self) -> Option<T> {
+        let now = self.clock.now();
+
+        let mut selected: Option<usize> = None;
+        for (index, candidate) in self.tasks.iter().enumerate() {
+            if candidate.next_run > now {
+                continue;
+            }
+            match selected {
+                Some(best) if self.tasks[best].priority >= candidate.priority => (),
+                _ => selected = Some(index),
+            }
+        }
+
+        let scheduled = &mut self.tasks[selected?];
+
+        if now - scheduled.next_run < scheduled.period {
+            scheduled.next_run += scheduled.period;
+        } else {
+            // A task which starts a whole period late has missed its deadline,
+            // instead of running it repeatedly to catch up, its schedule starts
+            // over from now. Tasks without a period run whenever nothing else
+            // is due, so they can't miss anything.
+            if scheduled.period > Duration::ZERO {
+                scheduled.missed_deadlines = scheduled.missed_deadlines.saturating_add(1);
+            }
+            scheduled.next_run = now + scheduled.period;
+        }
+
+        Some(scheduled.task)
+    }
+
+    /// Returns how many times the task started more than a period late.
+    pub fn missed_deadlines(&self, task: T) -> u16 {
+        self.tasks.iter()
+            .find(|scheduled| scheduled.task == task)
+            .map_or(0, |scheduled| scheduled.missed_deadlines)
+    }
+
+    /// Starts the schedule of all the tasks over, e.g. after the clock was
+    /// stopped during sleep.
+    pub fn restart(&mut self) {
+        let now = self.clock.now();
+        for scheduled in self.tasks.iter_mut() {
+            scheduled.next_run = now;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    // A clock which only moves when told to.
+    #[derive(Clone)]
+    struct FakeClock(Rc<Cell<u32>>);
+
+    impl FakeClock {
+        fn at(millis: u32) -> FakeClock {
+            FakeClock(Rc::new(Cell::new(millis)))
+        }
+
+        fn advance(&self, millis: u32) {
+            self.0.set(self.0.get().wrapping_add(millis));
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Instant {
+            Instant::from_millis(self.0.get())
+        }
+    }
+
+    #[derive(Copy, Clone, PartialEq, Debug)]
+    enum Task {
+        Input,
+        Tick,
+        Idle,
+    }
+
+    fn scheduler(clock: &FakeClock) -> Scheduler<Task, FakeClock, 3> {
+        let mut scheduler = Scheduler::new(clock.clone());
+        scheduler.register(Task::Idle, Duration::ZERO, 0).unwrap();
+        scheduler.register(Task::Tick, Duration::from_millis(10), 1).unwrap();
+        scheduler.register(Task::Input, Duration::from_millis(30), 2).unwrap();
+        scheduler
+    }
+
+    // Takes the due tasks until the idle one comes up.
+    fn run_due(scheduler: &mut Scheduler<Task, FakeClock, 3>) -> Vec<Task> {
+        let mut tasks = Vec::new();
+        while let Some(task) = scheduler.next_due() {
+            if task == Task::Idle {
+                break;
+            }
+            tasks.push(task);
+        }
+        tasks
+    }
+
+    #[test]
+    fn due_tasks_run_in_order_of_priority() {
+        let clock = FakeClock::at(0);
+        let mut scheduler = scheduler(&clock);
+        assert!(run_due(&mut scheduler) == [Task::Input, Task::Tick]);
+
+        clock.advance(10);
+        assert!(run_due(&mut scheduler) == [Task::Tick]);
+        clock.advance(20);
+        assert!(run_due(&mut scheduler) == [Task::Input, Task::Tick]);
+    }
+
+    #[test]
+    fn task_without_period_runs_whenever_nothing_else_is_due() {
+        let clock = FakeClock::at(0);
+        let mut scheduler = scheduler(&clock);
+        run_due(&mut scheduler);
+
+        for _ in 0..5 {
+            assert!(scheduler.next_due() == Some(Task::Idle));
+        }
+        assert!(scheduler.missed_deadlines(Task::Idle) == 0);
+    }
+
+    #[test]
+    fn ties_go_to_the_task_registered_first() {
+        let clock = FakeClock::at(0);
+        let mut scheduler: Scheduler<Task, FakeClock, 3> = Scheduler::new(clock.clone());
+        scheduler.register(Task::Tick, Duration::from_millis(10), 1).unwrap();
+        scheduler.register(Task::Input, Duration::from_millis(10), 1).unwrap();
+
+        assert!(scheduler.next_due() == Some(Task::Tick));
+        assert!(scheduler.next_due() == Some(Task::Input));
+        assert!(scheduler.next_due() == None);
+    }
+
+    #[test]
+    fn schedule_survives_clock_wraparound() {
+        let clock = FakeClock::at(u32::MAX - 15);
+        let mut scheduler = scheduler(&clock);
+        run_due(&mut scheduler);
+
+        clock.advance(10);
+        assert!(run_due(&mut scheduler) == [Task::Tick]);
+        clock.advance(10);
+        assert!(run_due(&mut scheduler) == [Task::Tick]);
+        clock.advance(10);
+        assert!(run_due(&mut scheduler) == [Task::Input, Task::Tick]);
+        assert!(scheduler.missed_deadlines(Task::Tick) == 0);
+    }
+
+    #[test]
+    fn tasks_started_a_period_late_miss_their_deadline() {
+        let clock = FakeClock::at(0);
+        let mut scheduler = scheduler(&clock);
+        run_due(&mut scheduler);
+
+        clock.advance(25);
+        assert!(run_due(&mut scheduler) == [Task::Tick]);
+        assert!(scheduler.missed_deadlines(Task::Tick) == 1);
+
+        // The schedule starts over rather than catching up.
+        clock.advance(9);
+        assert!(run_due(&mut scheduler) == [Task::Input]);
+        clock.advance(1);
+        assert!(run_due(&mut scheduler) == [Task::Tick]);
+        assert!(scheduler.missed_deadlines(Task::Input) == 0);
+    }
+
+    #[test]
+    fn full_scheduler_gives_task_back() {
+        let clock = FakeClock::at(0);
+        let mut scheduler = scheduler(&clock);
+        assert!(scheduler.register(Task::Idle, Duration::ZERO, 0) == Err(Task::Idle));
+    }
+}
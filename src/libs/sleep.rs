@@ -0,0 +1,40 @@
+//! Power-down sleep with wake up on a press of the controller switch (PC2)
+
+// Bits of the registers involved, see the ATmega328P datasheet.
+const ADEN: u8 = 1 << 7;
+const OCIE0A: u8 = 1 << 1;
+const PCIE1: u8 = 1 << 1;
+const PCINT10: u8 = 1 << 2;
+const SLEEP_ENABLE: u8 = 1 << 0;
+const SLEEP_MODE_POWER_DOWN: u8 = 0b_010 << 1;
+
+/// Puts the CPU into power-down sleep until the switch pin changes its state.
+/// The ADC and the timekeeping interrupt are disabled for the duration of
+/// the sleep and restored afterwards.
+pub fn sleep_until_switch_pressed() {
+    let dp = unsafe { arduino_hal::Peripherals::steal() };
+
+    let adc_control = dp.ADC.adcsra.read().bits();
+    let timer_interrupts = dp.TC0.timsk0.read().bits();
+    dp.ADC.adcsra.write(|w| unsafe { w.bits(adc_control & !ADEN) });
+    dp.TC0.timsk0.write(|w| unsafe { w.bits(timer_interrupts & !OCIE0A) });
+
+    // Enable the pin change interrupt of PC2 and clear any pending one.
+    dp.EXINT.pcmsk1.write(|w| unsafe { w.bits(PCINT10) });
+    dp.EXINT.pcifr.write(|w| unsafe { w.bits(PCIE1) });
+    dp.EXINT.pcicr.write(|w| unsafe { w.bits(PCIE1) });
+
+    dp.CPU.smcr.write(|w| unsafe { w.bits(SLEEP_MODE_POWER_DOWN | SLEEP_ENABLE) });
+    avr_device::asm::sleep();
+    dp.CPU.smcr.write(|w| unsafe { w.bits(0) });
+
+    dp.EXINT.pcicr.write(|w| unsafe { w.bits(0) });
+    dp.EXINT.pcmsk1.write(|w| unsafe { w.bits(0) });
+
+    dp.TC0.timsk0.write(|w| unsafe { w.bits(timer_interrupts) });
+    dp.ADC.adcsra.write(|w| unsafe { w.bits(adc_control) });
+}
+
+// The interrupt only needs to wake the CPU up, there is nothing to handle.
+#[avr_device::interrupt(atmega328p)]
+fn PCINT1() {}
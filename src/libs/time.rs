@@ -0,0 +1,171 @@
+//! Points in time and spans of time with millisecond resolution
+//!
+//! The millisecond counter wraps around after about 49 days. Instants are
+//! compared and subtracted with wrapping arithmetic, so the results stay
+//! correct across the wraparound as long as the two instants are less than
+//! about 24 days apart.
+
+use core::cmp::Ordering;
+use core::ops::{Add, AddAssign, Sub, Mul};
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Duration {
+    millis: u32,
+}
+
+impl Duration {
+    pub const ZERO: Duration = Duration { millis: 0 };
+
+    pub const fn from_millis(millis: u32) -> Duration {
+        Duration { millis }
+    }
+
+    pub const fn from_secs(secs: u32) -> Duration {
+        Duration { millis: secs * 1000 }
+    }
+
+    pub const fn as_millis(&self) -> u32 {
+        self.millis
+    }
+}
+
+impl Add for Duration {
+    type Output = Duration;
+
+    fn add(self, other: Duration) -> Duration {
+        Duration::from_millis(self.millis.saturating_add(other.millis))
+    }
+}
+
+/// Durations can't be negative, the subtraction saturates at zero.
+impl Sub for Duration {
+    type Output = Duration;
+
+    fn sub(self, other: Duration) -> Duration {
+        Duration::from_millis(self.millis.saturating_sub(other.millis))
+    }
+}
+
+impl Mul<u32> for Duration {
+    type Output = Duration;
+
+    fn mul(self, factor: u32) -> Duration {
+        Duration::from_millis(self.millis.saturating_mul(factor))
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Default)]
+pub struct Instant {
+    millis: u32,
+}
+
+impl Instant {
+    pub const fn from_millis(millis: u32) -> Instant {
+        Instant { millis }
+    }
+
+    pub const fn as_millis(&self) -> u32 {
+        self.millis
+    }
+
+    /// Returns the time elapsed from `earlier` to `self`, zero if `earlier` is
+    /// in fact later.
+    pub fn duration_since(&self, earlier: Instant) -> Duration {
+        match self.signed_difference(earlier) {
+            difference if difference > 0 => Duration::from_millis(difference as u32),
+            _                            => Duration::ZERO,
+        }
+    }
+
+    fn signed_difference(&self, other: Instant) -> i32 {
+        self.millis.wrapping_sub(other.millis) as i32
+    }
+}
+
+impl PartialOrd for Instant {
+    fn partial_cmp(&self, other: &Instant) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Instant {
+    fn cmp(&self, other: &Instant) -> Ordering {
+        self.signed_difference(*other).cmp(&0)
+    }
+}
+
+impl Add<Duration> for Instant {
+    type Output = Instant;
+
+    fn add(self, duration: Duration) -> Instant {
+        Instant::from_millis(self.millis.wrapping_add(duration.millis))
+    }
+}
+
+impl AddAssign<Duration> for Instant {
+    fn add_assign(&mut self, duration: Duration) {
+        *self = *self + duration;
+    }
+}
+
+impl Sub<Duration> for Instant {
+    type Output = Instant;
+
+    fn sub(self, duration: Duration) -> Instant {
+        Instant::from_millis(self.millis.wrapping_sub(duration.millis))
+    }
+}
+
+impl Sub for Instant {
+    type Output = Duration;
+
+    fn sub(self, earlier: Instant) -> Duration {
+        self.duration_since(earlier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BEFORE_WRAP: Instant = Instant::from_millis(u32::MAX - 99);
+    const AFTER_WRAP: Instant = Instant::from_millis(100);
+
+    #[test]
+    fn instants_keep_their_order_across_the_wraparound() {
+        assert!(BEFORE_WRAP < AFTER_WRAP);
+        assert!(AFTER_WRAP > BEFORE_WRAP);
+        assert!(BEFORE_WRAP.cmp(&BEFORE_WRAP) == Ordering::Equal);
+        assert!(Instant::from_millis(u32::MAX) < Instant::from_millis(0));
+    }
+
+    #[test]
+    fn elapsed_time_is_measured_across_the_wraparound() {
+        assert!(AFTER_WRAP - BEFORE_WRAP == Duration::from_millis(200));
+        assert!(AFTER_WRAP.duration_since(BEFORE_WRAP) == Duration::from_millis(200));
+        assert!(Instant::from_millis(0) - Instant::from_millis(u32::MAX) == Duration::from_millis(1));
+
+        // An instant in the future is no time ago.
+        assert!(BEFORE_WRAP - AFTER_WRAP == Duration::ZERO);
+        assert!(BEFORE_WRAP.duration_since(AFTER_WRAP) == Duration::ZERO);
+    }
+
+    #[test]
+    fn durations_move_instants_across_the_wraparound() {
+        assert!(BEFORE_WRAP + Duration::from_millis(200) == AFTER_WRAP);
+        assert!(AFTER_WRAP - Duration::from_millis(200) == BEFORE_WRAP);
+
+        let mut instant = BEFORE_WRAP;
+        instant += Duration::from_millis(100);
+        assert!(instant == Instant::from_millis(0));
+    }
+
+    #[test]
+    fn durations_saturate() {
+        let second = Duration::from_secs(1);
+        assert!(second - Duration::from_secs(2) == Duration::ZERO);
+        assert!(Duration::from_millis(u32::MAX) + second == Duration::from_millis(u32::MAX));
+        assert!(second * 3 == Duration::from_millis(3000));
+        assert!(second * u32::MAX == Duration::from_millis(u32::MAX));
+    }
+}
@@ -1,19 +1,33 @@
-
 use core::cell;
 
-const PRESCALER: u32 = 1024;
-const TIMER_COUNTS: u32 = 125;
+use arduino_hal::clock::Clock as CpuClock;
+
+use crate::traits::Clock;
+
+use super::time::Instant;
+
+// The frequency of the CPU clock, all the timer settings are derived from it.
+pub const CPU_FREQUENCY: u32 = <arduino_hal::DefaultClock as CpuClock>::FREQ;
 
-const MILLIS_INCREMENT: u32 = PRESCALER * TIMER_COUNTS / 16000;
+// Timer0 interrupts once every millisecond.
+const PRESCALER: u32 = 64;
+const TIMER_COUNTS: u32 = CPU_FREQUENCY / PRESCALER / 1000;
+
+const MILLIS_INCREMENT: u32 = PRESCALER * TIMER_COUNTS * 1000 / CPU_FREQUENCY;
+const MICROS_PER_TICK: u32 = PRESCALER * 1_000_000 / CPU_FREQUENCY;
+
+// The compare register of Timer0 only has 8 bits.
+const _: () = assert!(0 < TIMER_COUNTS && TIMER_COUNTS <= 256);
 
 static MILLIS_COUNTER: avr_device::interrupt::Mutex<cell::Cell<u32>> =
     avr_device::interrupt::Mutex::new(cell::Cell::new(0));
 
 pub fn millis_init(tc0: arduino_hal::pac::TC0) {
     // Configure the timer for the above interval (in CTC mode)
-    // and enable its interrupt.
+    // and enable its interrupt. The timer counts from zero up to and
+    // including the compare value.
     tc0.tccr0a.write(|w| w.wgm0().ctc());
-    tc0.ocr0a.write(|w| unsafe { w.bits(TIMER_COUNTS as u8) });
+    tc0.ocr0a.write(|w| unsafe { w.bits((TIMER_COUNTS - 1) as u8) });
     tc0.tccr0b.write(|w| match PRESCALER {
         8    => w.cs0().prescale_8(),
         64   => w.cs0().prescale_64(),
@@ -34,10 +48,38 @@ fn TIMER0_COMPA() {
     avr_device::interrupt::free(|cs| {
         let counter_cell = MILLIS_COUNTER.borrow(cs);
         let counter = counter_cell.get();
-        counter_cell.set(counter + MILLIS_INCREMENT);
+        counter_cell.set(counter.wrapping_add(MILLIS_INCREMENT));
     })
 }
 
-pub fn millis() -> u32 {
-    avr_device::interrupt::free(|cs| MILLIS_COUNTER.borrow(cs).get())
+pub fn now() -> Instant {
+    Instant::from_millis(avr_device::interrupt::free(|cs| MILLIS_COUNTER.borrow(cs).get()))
+}
+
+// The clock driven by Timer0, for the code which is generic over clocks.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        now()
+    }
+}
+
+/// Returns the number of microseconds since millis_init, it wraps around
+/// after about 71 minutes hence it is only fit for measuring short intervals.
+pub fn micros() -> u32 {
+    let tc0 = unsafe { &*arduino_hal::pac::TC0::ptr() };
+    avr_device::interrupt::free(|cs| {
+        let mut millis = MILLIS_COUNTER.borrow(cs).get();
+        let mut ticks = tc0.tcnt0.read().bits() as u32;
+
+        // The timer may have reached the compare value after the interrupts
+        // were disabled, in which case the counter is behind by one increment.
+        if tc0.tifr0.read().ocf0a().bit_is_set() {
+            ticks = tc0.tcnt0.read().bits() as u32;
+            millis = millis.wrapping_add(MILLIS_INCREMENT);
+        }
+
+        millis.wrapping_mul(1000).wrapping_add(ticks * MICROS_PER_TICK)
+    })
 }
@@ -0,0 +1,67 @@
+//! Square wave generation for a piezo buzzer using Timer2
+//!
+//! None of the timer output compare pins is free, hence the timer runs in CTC
+//! mode and toggles the buzzer pin from its compare interrupt.
+
+use arduino_hal::hal::port::PC5;
+use arduino_hal::port::Pin;
+use arduino_hal::port::mode::Output;
+
+use super::time_util::CPU_FREQUENCY;
+
+const BUZZER_PIN_MASK: u8 = 1 << 5; // PC5
+
+// Available prescalers of Timer2 with their clock select bits.
+const PRESCALERS: [(u32, u8); 7] =
+    [(1, 1), (8, 2), (32, 3), (64, 4), (128, 5), (256, 6), (1024, 7)];
+
+const WGM21: u8 = 1 << 1;
+const OCIE2A: u8 = 1 << 1;
+
+pub struct ToneGenerator {
+    timer: arduino_hal::pac::TC2,
+    _buzzer_pin: Pin<Output, PC5>,
+}
+
+impl ToneGenerator {
+    pub fn new(timer: arduino_hal::pac::TC2, buzzer_pin: Pin<Output, PC5>) -> Self {
+        timer.tccr2a.write(|w| unsafe { w.bits(WGM21) });
+        ToneGenerator { timer, _buzzer_pin: buzzer_pin }
+    }
+
+    /// Starts playing a square wave of the given frequency (Hz) until stopped.
+    pub fn play(&mut self, frequency: u16) {
+        if frequency == 0 {
+            return self.stop();
+        }
+
+        // The pin is toggled twice per period of the wave.
+        let half_period_ticks = CPU_FREQUENCY / (2 * frequency as u32);
+        let (prescaler, clock_select) = PRESCALERS.iter()
+            .find(|(prescaler, _)| half_period_ticks / prescaler <= 256)
+            .copied()
+            .unwrap_or(PRESCALERS[PRESCALERS.len() - 1]);
+        let compare_value = (half_period_ticks / prescaler).clamp(1, 256) - 1;
+
+        self.timer.ocr2a.write(|w| unsafe { w.bits(compare_value as u8) });
+        self.timer.tcnt2.write(|w| unsafe { w.bits(0) });
+        self.timer.tccr2b.write(|w| unsafe { w.bits(clock_select) });
+        self.timer.timsk2.write(|w| unsafe { w.bits(OCIE2A) });
+    }
+
+    pub fn stop(&mut self) {
+        self.timer.timsk2.write(|w| unsafe { w.bits(0) });
+        self.timer.tccr2b.write(|w| unsafe { w.bits(0) });
+        portc().portc.modify(|r, w| unsafe { w.bits(r.bits() & !BUZZER_PIN_MASK) });
+    }
+}
+
+#[avr_device::interrupt(atmega328p)]
+fn TIMER2_COMPA() {
+    // Writing a one to the input register of a pin toggles its output.
+    portc().pinc.write(|w| unsafe { w.bits(BUZZER_PIN_MASK) });
+}
+
+fn portc() -> &'static arduino_hal::pac::portc::RegisterBlock {
+    unsafe { &*arduino_hal::pac::PORTC::ptr() }
+}
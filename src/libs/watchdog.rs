@@ -0,0 +1,56 @@
+//! Watchdog timer, it resets the microcontroller unless it is fed in time
+
+// Bits of the watchdog control register and the MCU status register.
+const WDP0: u8 = 1 << 0;
+const WDP1: u8 = 1 << 1;
+const WDP2: u8 = 1 << 2;
+const WDE: u8 = 1 << 3;
+const WDCE: u8 = 1 << 4;
+const WDP3: u8 = 1 << 5;
+const WDRF: u8 = 1 << 3;
+
+#[derive(Copy, Clone)]
+pub enum WatchdogTimeout {
+    Ms500,
+    Ms1000,
+    Ms2000,
+    Ms4000,
+}
+
+impl WatchdogTimeout {
+    fn prescaler_bits(&self) -> u8 {
+        match self {
+            WatchdogTimeout::Ms500  => WDP2 | WDP0,
+            WatchdogTimeout::Ms1000 => WDP2 | WDP1,
+            WatchdogTimeout::Ms2000 => WDP2 | WDP1 | WDP0,
+            WatchdogTimeout::Ms4000 => WDP3,
+        }
+    }
+}
+
+pub fn watchdog_enable(timeout: WatchdogTimeout) {
+    write_control_register(WDE | timeout.prescaler_bits());
+}
+
+/// After a watchdog reset the watchdog stays enabled with the shortest
+/// timeout, so this needs to run as early as possible on startup.
+pub fn watchdog_disable() {
+    let cpu = unsafe { &*arduino_hal::pac::CPU::ptr() };
+    cpu.mcusr.modify(|r, w| unsafe { w.bits(r.bits() & !WDRF) });
+    write_control_register(0);
+}
+
+pub fn watchdog_feed() {
+    avr_device::asm::wdr();
+}
+
+// Changing the watchdog configuration requires setting the change enable bit
+// and writing the new value within the following four clock cycles.
+fn write_control_register(value: u8) {
+    let wdt = unsafe { &*arduino_hal::pac::WDT::ptr() };
+    avr_device::interrupt::free(|_| {
+        avr_device::asm::wdr();
+        wdt.wdtcsr.write(|w| unsafe { w.bits(WDCE | WDE) });
+        wdt.wdtcsr.write(|w| unsafe { w.bits(value) });
+    });
+}
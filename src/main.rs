@@ -1,102 +1,316 @@
 #![no_std]
 #![no_main]
-#![feature(abi_avr_interrupt)]
 #![feature(trait_upcasting)]
 #![allow(incomplete_features)]
-#![feature(asm_experimental_arch)]
 
+use core::cell::RefCell;
 use core::panic::PanicInfo;
 
 extern crate arduino_hal;
 extern crate avr_device;
 extern crate embedded_hal;
-extern crate arrayvec;
-extern crate oorandom;
+#[cfg(any(feature = "panic-serial", feature = "telemetry"))]
+extern crate ufmt;
+extern crate snake_console;
 
-mod traits;
-mod common;
-mod game_engine;
+// The game itself lives in the library, the firmware adds the hardware it
+// runs on.
+use snake_console::{traits, common, game_engine, user_interface, internal_representation, libs};
+
+#[macro_use]
 mod matrix_view;
+mod buzzer;
+#[cfg(not(any(feature = "dpad", feature = "rotary-encoder")))]
 mod analog_stick;
-mod user_interface;
-mod internal_representation;
-mod libs;
 
-use common::CONTROLLER_POLLING_INTERVAL;
+use common::{CONTROLLER_POLLING_INTERVAL, GAME_TICK_INTERVAL, OBSERVERS_UPDATE_INTERVAL};
+#[cfg(feature = "telemetry")]
+use common::TELEMETRY_INTERVAL;
 use internal_representation::controller_input::ControllerInput;
-use libs::time_util::millis_init;
-use matrix_view::GroundPins;
-use traits::{TimedRunnable, Controller, ControllerListener};
-use libs::shift_register::ShiftRegister;
+use libs::scheduler::Scheduler;
+use libs::event_dispatcher::EventDispatcher;
+use libs::time::Duration;
+use libs::time_util::{millis_init, SystemClock};
+use libs::sleep::sleep_until_switch_pressed;
+use libs::watchdog::{watchdog_enable, watchdog_disable, watchdog_feed, WatchdogTimeout};
+use traits::{Controller, ControllerListener, View, Runnable};
 
-use crate::analog_stick::AnalogStick;
 use crate::game_engine::GameEngine;
-use crate::matrix_view::GameView;
+use crate::buzzer::Buzzer;
+use crate::libs::tone::ToneGenerator;
+use crate::libs::eeprom::Eeprom;
+use crate::internal_representation::game_event::GameEvent;
+use crate::internal_representation::settings::{Settings, SettingsStorage};
+use crate::internal_representation::sound_director::SoundDirector;
+use crate::internal_representation::statistics::{Statistics, StatisticsRecorder};
+use crate::internal_representation::entropy::{EntropyPool, count_boot};
+
+#[cfg(not(feature = "dpad"))]
+use libs::time_util::micros;
+#[cfg(not(feature = "dpad"))]
+use crate::internal_representation::entropy::gather_noise;
+#[cfg(not(any(feature = "dpad", feature = "rotary-encoder")))]
+use crate::analog_stick::AnalogStick;
+#[cfg(not(any(feature = "dpad", feature = "rotary-encoder")))]
+use crate::internal_representation::calibration::Calibration;
+#[cfg(feature = "dpad")]
+use snake_console::dpad::DPad;
+#[cfg(feature = "rotary-encoder")]
+use snake_console::rotary_encoder::RotaryEncoder;
+#[cfg(feature = "rotary-encoder")]
+use crate::libs::encoder_sampler;
+
+#[cfg(all(feature = "dpad", feature = "rotary-encoder"))]
+compile_error!("Only one of the controller features can be enabled at a time.");
+
+// The tasks run by the main loop, the higher the priority the sooner a task
+// runs when several of them are due.
+#[derive(Copy, Clone, PartialEq)]
+enum Task {
+    PollInput,
+    GameTick,
+    UpdateObservers,
+    #[cfg(feature = "telemetry")]
+    Telemetry,
+    RefreshDisplay,
+}
+
+#[cfg(not(feature = "telemetry"))]
+const TASKS_NUMBER: usize = 4;
+#[cfg(feature = "telemetry")]
+const TASKS_NUMBER: usize = 5;
+
+// The display is refreshed whenever nothing else is due.
+const TASK_SCHEDULE: [(Task, Duration, u8); TASKS_NUMBER] = [
+    (Task::PollInput,       CONTROLLER_POLLING_INTERVAL, 4),
+    (Task::GameTick,        GAME_TICK_INTERVAL,          3),
+    (Task::UpdateObservers, OBSERVERS_UPDATE_INTERVAL,  2),
+    #[cfg(feature = "telemetry")]
+    (Task::Telemetry,       TELEMETRY_INTERVAL,          1),
+    (Task::RefreshDisplay,  Duration::ZERO,              0),
+];
+
+// The number of subsystems which can observe the game events.
+const MAX_OBSERVERS: usize = 4;
+
+// The number of words of analog noise gathered at boot, each of them takes
+// 32 readings.
+#[cfg(not(feature = "dpad"))]
+const NOISE_WORDS: usize = 4;
+
+// The time after which the main loop is considered to hang.
+const WATCHDOG_TIMEOUT: WatchdogTimeout = WatchdogTimeout::Ms2000;
 
+// The time for which the error screen is shown before resetting.
+const PANIC_SCREEN_TIMEOUT: WatchdogTimeout = WatchdogTimeout::Ms4000;
+
+// On panic, the error screen is shown on the matrix and, if enabled, the
+// location of the panic is reported over the serial port. The console then
+// resets itself by letting the watchdog run out.
 #[panic_handler]
 fn panic(_info: &PanicInfo) -> ! {
-    loop {}
+    avr_device::interrupt::disable();
+
+    let peripherals = unsafe { arduino_hal::Peripherals::steal() };
+    let pins = arduino_hal::pins!(peripherals);
+
+    #[cfg(feature = "panic-serial")]
+    {
+        let mut serial = arduino_hal::default_serial!(peripherals, pins, 57600);
+        ufmt::uwriteln!(&mut serial, "Panicked!\r").ok();
+        if let Some(location) = _info.location() {
+            ufmt::uwriteln!(&mut serial, "  at {}:{}:{}\r",
+                location.file(), location.line(), location.column()).ok();
+        }
+    }
+
+    watchdog_enable(PANIC_SCREEN_TIMEOUT);
+
+    let mut view = create_game_view!(pins);
+    view.update(user_interface::print_error());
+    loop {
+        view.run_once();
+    }
 }
 
 #[arduino_hal::entry]
 fn main() -> ! {
+    watchdog_disable();
+
     loop {
         let peripherals = arduino_hal::Peripherals::take().unwrap();
         let pins = arduino_hal::pins!(peripherals);
 
-        // Enable the millis() function
+        // Enable the time keeping functions
         millis_init(peripherals.TC0);
         unsafe { avr_device::interrupt::enable() }
 
         // Initialise the view.
-        let clock_pin = pins.d10.into_output();
-        let latch_pin = pins.d11.into_output();
-        let data_pin = pins.d12.into_output();
-        let ground_0_supply = pins.d13.into_output();
-
-        let ground_pins = GroundPins::new(
-            pins.d2.into_output_high(),
-            pins.d3.into_output_high(),
-            pins.d4.into_output_high(),
-            pins.d5.into_output_high(),
-            pins.d6.into_output_high(),
-            pins.d7.into_output_high(),
-            pins.d8.into_output_high(),
-            pins.d9.into_output_high());
-
-        let shift_register = ShiftRegister::new(
-            clock_pin,
-            latch_pin,
-            data_pin,
-            ground_0_supply);
-
-        let mut view = GameView::new(shift_register, ground_pins);
+        let mut view = create_game_view!(pins);
 
-        // Initialise the engine.
+        // Initialise the sound.
+        let tone_generator = ToneGenerator::new(peripherals.TC2, pins.a5.into_output());
+        let mut sound = SoundDirector::new(Buzzer::new(tone_generator));
+
+        // The converter reads both the noise and the analog stick.
+        #[cfg(not(feature = "dpad"))]
         let mut ad_converter = arduino_hal::Adc::new(
             peripherals.ADC, Default::default());
 
-        let random_noise_pin = pins.a3.into_analog_input(&mut ad_converter);
-        let random_seed = random_noise_pin.analog_read(&mut ad_converter);
-        let mut engine = GameEngine::new(&mut view, random_seed);
+        // The floating pin A3 picks up noise, together with the boot counter
+        // it makes up the initial entropy. The d-pad needs A3 for a button,
+        // hence it only relies on the boot counter and the timing of the input.
+        let mut entropy = EntropyPool::new();
+        entropy.mix(count_boot(&mut Eeprom::new()));
+        #[cfg(not(feature = "dpad"))]
+        {
+            let random_noise_pin = pins.a3.into_analog_input(&mut ad_converter);
+            for _ in 0..NOISE_WORDS {
+                let noise = gather_noise(|| random_noise_pin.analog_read(&mut ad_converter));
+                entropy.mix(noise);
+                entropy.mix(micros());
+            }
+        }
+
+        // Initialise the controller. The stick may need the screen to be
+        // calibrated, hence it comes before the engine takes over the view.
+        #[cfg(not(any(feature = "dpad", feature = "rotary-encoder")))]
+        let mut controller = {
+            let x_pin = pins.a0.into_analog_input(&mut ad_converter);
+            let y_pin = pins.a1.into_analog_input(&mut ad_converter);
+            let switch_pin = pins.a2.into_pull_up_input();
+
+            let mut eeprom = Eeprom::new();
+            let stored_calibration = Calibration::load(&eeprom);
+
+            let mut controller = AnalogStick::new(
+                x_pin,
+                y_pin,
+                switch_pin,
+                ad_converter,
+                stored_calibration.unwrap_or_default());
+
+            // Holding the switch down while booting recalibrates the stick,
+            // the sampling starts once the switch is released. Then the stick
+            // is pushed the way the arrow points, which tells how it is mounted.
+            if controller.is_switch_pressed() || stored_calibration.is_none() {
+                while controller.is_switch_pressed() {}
+                arduino_hal::delay_ms(500);
+                controller.calibrate();
+                view.update(user_interface::print_up_arrow());
+                controller.orient(&mut || view.run_once()).save(&mut eeprom);
+            }
+            controller
+        };
 
-        // Initialise the controller.
-        let x_pin = pins.a0.into_analog_input(&mut ad_converter);
-        let y_pin = pins.a1.into_analog_input(&mut ad_converter);
-        let switch_pin = pins.a2.into_pull_up_input();
+        // All the digital pins drive the matrix and A5 drives the buzzer,
+        // hence the right button takes the noise pin A3. The serial port pins
+        // D0 and D1 are kept free for the reports of panics and telemetry.
+        #[cfg(feature = "dpad")]
+        let mut controller = DPad::new(
+            pins.a0.into_pull_up_input(),
+            pins.a1.into_pull_up_input(),
+            pins.a4.into_pull_up_input(),
+            pins.a3.into_pull_up_input(),
+            pins.a2.into_pull_up_input());
 
-        let mut controller = AnalogStick::new(
-            x_pin,
-            y_pin,
-            switch_pin,
-            ad_converter);
+        #[cfg(feature = "rotary-encoder")]
+        let mut controller = {
+            encoder_sampler::sampling_init(
+                peripherals.TC1,
+                pins.a0.into_pull_up_input(),
+                pins.a1.into_pull_up_input());
+            RotaryEncoder::new(pins.a2.into_pull_up_input(), encoder_sampler::take_turn)
+        };
+
+        // Initialise the engine.
+        let settings = Settings::load(&Eeprom::new()).unwrap_or_default();
+        let statistics = RefCell::new(Statistics::load(&Eeprom::new()).unwrap_or_default());
+        let mut engine = GameEngine::new(&mut view, settings, &statistics, entropy);
+
+        let mut settings_storage = SettingsStorage::new(Eeprom::new());
+        let mut statistics_recorder = StatisticsRecorder::new(&statistics, Eeprom::new());
+        let mut observers: EventDispatcher<MAX_OBSERVERS> = EventDispatcher::new();
+        observers.subscribe(&mut sound).ok();
+        observers.subscribe(&mut settings_storage).ok();
+        observers.subscribe(&mut statistics_recorder).ok();
+
+        #[cfg(feature = "telemetry")]
+        let mut serial = arduino_hal::default_serial!(peripherals, pins, 57600);
+
+        let mut scheduler: Scheduler<Task, SystemClock, TASKS_NUMBER> =
+            Scheduler::new(SystemClock);
+        for &(task, period, priority) in TASK_SCHEDULE.iter() {
+            scheduler.register(task, period, priority).ok();
+        }
+
+        // From now on, if the main loop hangs the watchdog resets the console.
+        watchdog_enable(WATCHDOG_TIMEOUT);
 
         loop {
-            let input: ControllerInput = controller.read_input();
-            engine.on_input(input);
-            engine.run_for(CONTROLLER_POLLING_INTERVAL);
+            watchdog_feed();
+
+            // The watchdog would reset the console while it sleeps.
+            if engine.is_idle() {
+                engine.go_to_sleep();
+                dispatch_events(&mut engine, &mut observers, &mut controller);
+                watchdog_disable();
+                sleep_until_switch_pressed();
+                watchdog_enable(WATCHDOG_TIMEOUT);
+                engine.wake_up();
+                scheduler.restart();
+            }
+
+            match scheduler.next_due() {
+                Some(Task::PollInput)       => {
+                    let input: ControllerInput = controller.read_input();
+                    engine.on_input(input);
+                },
+                Some(Task::GameTick)        => engine.run_once(),
+                Some(Task::UpdateObservers) => observers.run_once(),
+                #[cfg(feature = "telemetry")]
+                Some(Task::Telemetry)       => report_missed_deadlines(&mut serial, &scheduler),
+                Some(Task::RefreshDisplay)  => engine.refresh_display(),
+                None                        => (),
+            }
+            dispatch_events(&mut engine, &mut observers, &mut controller);
         }
     }
 }
 
+// Hands the events emitted by the engine over to the observers. The
+// controller is polled by the main loop, hence it can't be subscribed and
+// picks up its settings here.
+fn dispatch_events<const N: usize>(
+    engine: &mut GameEngine,
+    observers: &mut EventDispatcher<N>,
+    controller: &mut dyn Controller) {
+
+    while let Some(event) = engine.poll_event() {
+        if let GameEvent::SettingsChanged { settings } = event {
+            controller.set_sensitivity(settings.sensitivity());
+        }
+        observers.dispatch(event);
+    }
+}
+
+// Reports how many times each task of the main loop started more than a
+// period late since the console was switched on.
+#[cfg(feature = "telemetry")]
+fn report_missed_deadlines<W: ufmt::uWrite>(
+    serial: &mut W,
+    scheduler: &Scheduler<Task, SystemClock, TASKS_NUMBER>) {
+
+    for &(task, _, _) in TASK_SCHEDULE.iter() {
+        let name = match task {
+            Task::PollInput       => "input",
+            Task::GameTick        => "tick",
+            Task::UpdateObservers => "observers",
+            Task::Telemetry       => "telemetry",
+            Task::RefreshDisplay  => "display",
+        };
+        ufmt::uwrite!(serial, "{}: {} ", name, scheduler.missed_deadlines(task)).ok();
+    }
+    ufmt::uwriteln!(serial, "\r").ok();
+}
 
@@ -5,15 +5,41 @@ use arduino_hal::port::mode::Output;
 use arduino_hal::hal::port::{PB0, PB1, PB2, PB3, PB4, PB5};
 use arduino_hal::hal::port::{PD2, PD3, PD4, PD5, PD6, PD7};
 
-use crate::common::BOARD_SIZE;
-use crate::internal_representation::game_board::BoardCell;
+use crate::common::{BOARD_SIZE, MAX_BRIGHTNESS};
+use crate::internal_representation::frame::Frame;
 use crate::libs::shift_register::ShiftRegister;
 use crate::traits::{View, Runnable};
 
-pub const SCREEN_REFRESH_INTERVAL: u32 = 150; // 150 microseconds.
+// The time for which each row of the matrix is lit during a single refresh.
+// All leds of a row are lit at once, so a full frame always takes
+// BOARD_SIZE * ROW_ON_TIME regardless of how many cells are lit.
+pub const ROW_ON_TIME: u32 = 1000; // 1000 microseconds.
+
+// Builds the view out of the pins of the board which drive the matrix. It is a
+// macro rather than a function so that the remaining pins can still be used.
+macro_rules! create_game_view {
+    ($pins: ident) => {
+        $crate::matrix_view::GameView::new(
+            $crate::libs::shift_register::ShiftRegister::new(
+                $pins.d10.into_output(),  // clock
+                $pins.d11.into_output(),  // latch
+                $pins.d12.into_output(),  // data
+                $pins.d13.into_output()), // ground 0 supply
+            $crate::matrix_view::GroundPins::new(
+                $pins.d2.into_output_high(),
+                $pins.d3.into_output_high(),
+                $pins.d4.into_output_high(),
+                $pins.d5.into_output_high(),
+                $pins.d6.into_output_high(),
+                $pins.d7.into_output_high(),
+                $pins.d8.into_output_high(),
+                $pins.d9.into_output_high()))
+    }
+}
 
 pub struct GameView {
-    screen: [[BoardCell; BOARD_SIZE]; BOARD_SIZE],
+    screen: Frame,
+    brightness: u8,
     shift_register: ShiftRegister<
         Pin<Output, PB2>,
         Pin<Output, PB3>,
@@ -34,6 +60,7 @@ impl GameView {
 
         GameView {
             screen: Default::default(),
+            brightness: MAX_BRIGHTNESS,
             shift_register,
             ground_pins,
         }
@@ -41,35 +68,43 @@ impl GameView {
 }
 
 impl View for GameView {
-    fn update(&mut self, screen: [[BoardCell; BOARD_SIZE]; BOARD_SIZE]) -> () {
+    fn update(&mut self, screen: Frame) -> () {
         self.screen = screen;
     }
 
+    fn set_brightness(&mut self, level: u8) -> () {
+        self.brightness = level.clamp(1, MAX_BRIGHTNESS);
+    }
 }
 
 impl Runnable for GameView {
     fn run_once(&mut self) -> () {
+        // Dimmer rows are lit for a part of ROW_ON_TIME and stay dark for the
+        // rest of it, so that the refresh rate doesn't depend on brightness.
+        let lit_time = ROW_ON_TIME * self.brightness as u32 / MAX_BRIGHTNESS as u32;
+        let dark_time = ROW_ON_TIME - lit_time;
+
         let mut outputs = self.shift_register.decompose();
         for i in 0..BOARD_SIZE {
-            outputs[i].set_high().ok(); // Add voltage to the ith row of the matrix
-
-
-            // Set the corresponding ground pin to low to complete the circuit
-            // and make the led light up. In order to light up the led with
-            // coordinates (x, y) we send voltage on the x-th matrix row pin and
-            // enable (by setting to low) the y-th ground which lights the led.
+            // In order to light up the led with coordinates (x, y) we send
+            // voltage on the x-th matrix row pin and enable (by setting to low)
+            // the y-th ground which completes the circuit. We enable the
+            // grounds of all lit cells of the row first so that the whole
+            // row lights up at once for the same amount of time.
             for j in 0..BOARD_SIZE {
-
-                if !self.screen[j][i].is_empty() {
+                if self.screen.is_lit(i, j) {
                     self.ground_pins.set_pin_low(j)
                 }
-
-                arduino_hal::delay_us(SCREEN_REFRESH_INTERVAL);
-                self.ground_pins.disconnect_ground();
-
             }
 
+            outputs[i].set_high().ok(); // Add voltage to the ith row of the matrix
+            arduino_hal::delay_us(lit_time);
             outputs[i].set_low().ok();
+
+            self.ground_pins.disconnect_ground();
+            if dark_time > 0 {
+                arduino_hal::delay_us(dark_time);
+            }
         }
     }
 }
@@ -0,0 +1,111 @@
+use embedded_hal::digital::v2::InputPin;
+
+use crate::libs::time_util::now;
+use crate::traits::Controller;
+
+use crate::internal_representation::button::ButtonDebouncer;
+use crate::internal_representation::controller_input::ControllerInput;
+use crate::internal_representation::direction::Turn;
+
+// Quadrature rotary encoder with a push switch. Turning the knob turns the
+// snake left or right relative to the direction it is heading in. The switch
+// is expected to connect to the ground, so its pin needs to be pulled up.
+//
+// The signals of the knob change far quicker than the controller is polled,
+// hence they are sampled and decoded elsewhere (see libs::encoder_sampler)
+// and the controller only picks up the turns.
+pub struct RotaryEncoder<SwitchPin>
+    where SwitchPin: InputPin,
+{
+    switch_pin: SwitchPin,
+    take_turn: fn() -> Option<Turn>,
+    button: ButtonDebouncer,
+}
+
+impl<SwitchPin> RotaryEncoder<SwitchPin>
+    where SwitchPin: InputPin,
+{
+    pub fn new(switch_pin: SwitchPin, take_turn: fn() -> Option<Turn>) -> Self {
+        RotaryEncoder {
+            switch_pin,
+            take_turn,
+            button: ButtonDebouncer::new() }
+    }
+}
+
+impl<SwitchPin> Controller for RotaryEncoder<SwitchPin>
+    where SwitchPin: InputPin,
+{
+    fn read_input(&mut self) -> ControllerInput {
+        let button = self.button.update(is_low(&self.switch_pin), now());
+        let turn = (self.take_turn)();
+
+        ControllerInput::with_turn(button, turn)
+    }
+
+    // Each detent of the encoder is a single turn.
+    fn set_sensitivity(&mut self, _level: u8) -> () {}
+}
+
+fn is_low<Pin: InputPin>(pin: &Pin) -> bool {
+    pin.is_low().unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::Cell;
+    use std::convert::Infallible;
+    use std::rc::Rc;
+
+    use crate::internal_representation::button::ButtonEvent;
+    use crate::libs::time::Duration;
+    use crate::libs::time_util::advance;
+
+    // A switch which is pressed while its shared flag is set.
+    #[derive(Clone, Default)]
+    struct FakeSwitch(Rc<Cell<bool>>);
+
+    impl InputPin for FakeSwitch {
+        type Error = Infallible;
+
+        fn is_high(&self) -> Result<bool, Infallible> {
+            Ok(!self.0.get())
+        }
+
+        fn is_low(&self) -> Result<bool, Infallible> {
+            Ok(self.0.get())
+        }
+    }
+
+    #[test]
+    fn turns_are_picked_up_from_the_sampler() {
+        let mut encoder = RotaryEncoder::new(FakeSwitch::default(), || Some(Turn::Left));
+        let input = encoder.read_input();
+        assert!(input.turn == Some(Turn::Left) && input.button == None);
+
+        let mut encoder = RotaryEncoder::new(FakeSwitch::default(), || None);
+        assert!(!encoder.read_input().is_active());
+    }
+
+    #[test]
+    fn held_switch_is_long_press() {
+        let switch = FakeSwitch::default();
+        let mut encoder = RotaryEncoder::new(switch.clone(), || None);
+        let mut events = Vec::new();
+
+        switch.0.set(true);
+        for _ in 0..70 {
+            advance(Duration::from_millis(10));
+            events.extend(encoder.read_input().button);
+        }
+        switch.0.set(false);
+        for _ in 0..50 {
+            advance(Duration::from_millis(10));
+            events.extend(encoder.read_input().button);
+        }
+
+        assert!(events == [ButtonEvent::Press, ButtonEvent::LongPress, ButtonEvent::Release]);
+    }
+}
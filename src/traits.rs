@@ -1,7 +1,10 @@
-use crate::common::BOARD_SIZE;
-use crate::internal_representation::game_board::BoardCell;
-use crate::libs::time_util::millis;
+use crate::internal_representation::frame::Frame;
+use crate::internal_representation::melody::Tune;
+use crate::internal_representation::sound_effect::SoundEffect;
+use crate::libs::time::{Instant, Duration};
+use crate::libs::time_util::now;
 use crate::internal_representation::controller_input::ControllerInput;
+use crate::internal_representation::game_event::GameEvent;
 
 
 pub trait Runnable {
@@ -9,7 +12,7 @@ pub trait Runnable {
 }
 
 pub trait TimedRunnable : Runnable {
-    fn run_for(&mut self, miliseconds: u32) -> ();
+    fn run_for(&mut self, duration: Duration) -> ();
 }
 
 pub trait ControllerListener: TimedRunnable {
@@ -17,20 +20,49 @@ pub trait ControllerListener: TimedRunnable {
 }
 
 pub trait View: TimedRunnable {
-    fn update(&mut self, screen: [[BoardCell; BOARD_SIZE]; BOARD_SIZE]) -> ();
+    fn update(&mut self, screen: Frame) -> ();
+    fn set_brightness(&mut self, level: u8) -> ();
+}
+
+pub trait Sound: Runnable {
+    fn play(&mut self, effect: SoundEffect) -> ();
+    fn play_music(&mut self, tune: Tune, tempo: u16) -> ();
+    fn stop_music(&mut self) -> ();
+    fn set_muted(&mut self, muted: bool) -> ();
+}
+
+pub trait GameObserver: Runnable {
+    fn on_event(&mut self, event: GameEvent) -> ();
 }
 
 pub trait Controller {
     fn read_input(&mut self) -> ControllerInput;
+    fn set_sensitivity(&mut self, level: u8) -> ();
+}
+
+// A list of items with values which can be browsed and edited in a menu.
+pub trait MenuModel {
+    fn len(&self) -> usize;
+    fn icon(&self, index: usize) -> Frame;
+    fn value(&self, index: usize) -> u8;
+    fn value_count(&self, index: usize) -> u8;
+    fn set_value(&mut self, index: usize, value: u8) -> ();
+}
+
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+pub trait Storage {
+    fn read_byte(&self, address: u16) -> u8;
+    fn write_byte(&mut self, address: u16, value: u8) -> ();
 }
 
 impl<T> TimedRunnable for T where T: Runnable {
-    fn run_for(&mut self, miliseconds: u32) -> () {
-        let time_slice_start = millis();
-        let mut current_time = millis();
-        while current_time - time_slice_start < miliseconds {
+    fn run_for(&mut self, duration: Duration) -> () {
+        let time_slice_start = now();
+        while now() - time_slice_start < duration {
             self.run_once();
-            current_time = millis();
         }
     }
 }
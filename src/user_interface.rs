@@ -1,80 +1,483 @@
-use crate::common::{BOARD_SIZE, MAX_SCORE};
-use crate::internal_representation::game_board::BoardCell;
-use crate::internal_representation::game_state::GameSpeed;
+use crate::common::BOARD_SIZE;
+use crate::libs::time::{Instant, Duration};
+use crate::traits::MenuModel;
+use crate::internal_representation::button::ButtonEvent;
+use crate::internal_representation::controller_input::ControllerInput;
+use crate::internal_representation::direction::Direction;
+use crate::internal_representation::frame::Frame;
+use crate::internal_representation::settings::Setting;
+use crate::internal_representation::statistics::{Statistic, Statistics, STATISTICS};
+use crate::internal_representation::game_state::{GameMode, GameSpeed, GAME_MODES, GAME_MODES_NUMBER};
+use crate::internal_representation::challenge::CODE_DIGITS;
+use crate::internal_representation::hazard::Hazard;
 
-pub fn print_score(score: u8) -> [[BoardCell; BOARD_SIZE]; BOARD_SIZE] {
-    assert!(score as usize <= MAX_SCORE);
+// The time it takes a long score to scroll by one column.
+const SCROLL_STEP: Duration = Duration::from_millis(120);
 
-    let digits = [ZERO, ONE, TWO, THREE, FOUR, FIVE, SIX, SEVEN, EIGHT, NINE];
-    let first_digit = digits[score as usize / 10_usize];
-    let second_digit = digits[score as usize % 10_usize];
+// The digits are four columns wide, hence two of them fit on the screen.
+const DIGIT_WIDTH: usize = BOARD_SIZE / 2;
+const MAX_SCORE_DIGITS: usize = 5;
 
-    print_lines(join_pictures(first_digit, second_digit))
+// The glyphs of the hexadecimal digits followed by the dash, which separates
+// the code of a challenge from its score.
+const GLYPHS: [[u8; BOARD_SIZE]; 17] = [
+    ZERO, ONE, TWO, THREE, FOUR, FIVE, SIX, SEVEN, EIGHT, NINE,
+    HEX_A, HEX_B, HEX_C, HEX_D, HEX_E, HEX_F, DASH,
+];
+const HEX_DIGITS: usize = 16;
+const DASH_GLYPH: u8 = 16;
+const MAX_RESULT_GLYPHS: usize = CODE_DIGITS + 1 + MAX_SCORE_DIGITS;
+
+// The time for which the icon of a statistic is shown before its value.
+const STATISTIC_ICON_DURATION: Duration = Duration::from_secs(1);
+
+// The time for which the best score of a game mode is shown at least, after
+// the icon of the mode.
+const BEST_SCORE_DURATION: Duration = Duration::from_secs(2);
+
+// Scores of up to two digits are shown as they are, longer ones scroll
+// through the screen from right to left over and over. The time for which
+// the score has been shown picks the part of it which is visible.
+pub fn print_score(score: u16, time_shown: Duration) -> Frame {
+    if score < 100 {
+        let first_digit = GLYPHS[score as usize / 10_usize];
+        let second_digit = GLYPHS[score as usize % 10_usize];
+        return Frame::new(join_pictures(first_digit, second_digit));
+    }
+
+    let (score_digits, length) = split_digits(score);
+    scroll_glyphs(&score_digits[..length], time_shown)
+}
+
+// The time it takes the score to scroll through the screen once, zero if it
+// doesn't need to scroll.
+pub fn score_scroll_duration(score: u16) -> Duration {
+    match score {
+        0..=99 => Duration::ZERO,
+        _      => SCROLL_STEP * scroll_length(split_digits(score).1) as u32,
+    }
+}
+
+// The code of a challenge and the score, separated by a dash, scroll through
+// the screen together.
+pub fn print_challenge_result(code: u16, score: u16, time_shown: Duration) -> Frame {
+    let (glyphs, length) = challenge_result_glyphs(code, score);
+    scroll_glyphs(&glyphs[..length], time_shown)
+}
+
+pub fn challenge_result_scroll_duration(code: u16, score: u16) -> Duration {
+    SCROLL_STEP * scroll_length(challenge_result_glyphs(code, score).1) as u32
+}
+
+fn challenge_result_glyphs(code: u16, score: u16) -> ([u8; MAX_RESULT_GLYPHS], usize) {
+    let mut glyphs = [0; MAX_RESULT_GLYPHS];
+    glyphs[..CODE_DIGITS].copy_from_slice(&split_code(code));
+    glyphs[CODE_DIGITS] = DASH_GLYPH;
+
+    let (score_digits, length) = split_digits(score);
+    glyphs[CODE_DIGITS + 1..][..length].copy_from_slice(&score_digits[..length]);
+    (glyphs, CODE_DIGITS + 1 + length)
+}
+
+// Scrolls the glyphs through the screen from right to left over and over,
+// the time for which they have been shown picks the visible part.
+fn scroll_glyphs(glyphs: &[u8], time_shown: Duration) -> Frame {
+    let scroll_length = scroll_length(glyphs.len()) as u32;
+    let offset = (time_shown.as_millis() / SCROLL_STEP.as_millis()) % scroll_length;
+
+    // Each row of the glyphs is laid out between two blank screens, of which
+    // the visible window is cut out. Column zero is the most significant bit.
+    let total_width = (2 * BOARD_SIZE + glyphs.len() * DIGIT_WIDTH) as u32;
+    let shift = total_width - BOARD_SIZE as u32 - offset;
+
+    let mut rows = [0; BOARD_SIZE];
+    for (y, row) in rows.iter_mut().enumerate() {
+        let mut strip: u64 = 0;
+        for glyph in glyphs.iter() {
+            strip = (strip << DIGIT_WIDTH) | GLYPHS[*glyph as usize][y] as u64;
+        }
+        strip <<= BOARD_SIZE;
+        *row = (strip >> shift) as u8;
+    }
+    Frame::new(rows)
+}
+
+fn scroll_length(glyph_count: usize) -> usize {
+    BOARD_SIZE + glyph_count * DIGIT_WIDTH
+}
+
+// Returns the hexadecimal digits of the code, most significant first.
+fn split_code(code: u16) -> [u8; CODE_DIGITS] {
+    let mut digits = [0; CODE_DIGITS];
+    for (i, digit) in digits.iter_mut().enumerate() {
+        let shift = 4 * (CODE_DIGITS - 1 - i);
+        *digit = ((code >> shift) & 0xF) as u8;
+    }
+    digits
+}
+
+// Returns the decimal digits of the number, most significant first, and how
+// many of them there are.
+fn split_digits(mut number: u16) -> ([u8; MAX_SCORE_DIGITS], usize) {
+    let mut digits = [0; MAX_SCORE_DIGITS];
+    let mut length = 0;
+    loop {
+        digits[length] = (number % 10) as u8;
+        length += 1;
+        number /= 10;
+        if number == 0 {
+            break;
+        }
+    }
+    digits[..length].reverse();
+    (digits, length)
+}
+
+// Prints a single decimal or hexadecimal digit in the middle of the screen.
+pub fn print_digit(digit: u8) -> Frame {
+    assert!((digit as usize) < HEX_DIGITS);
+
+    let mut centered = GLYPHS[digit as usize];
+    for row in centered.iter_mut() {
+        *row <<= BOARD_SIZE / 4;
+    }
+    Frame::new(centered)
+}
+
+pub fn print_trophy() -> Frame {
+    Frame::new(TROPHY)
+}
+
+pub fn print_selection_arrows() -> Frame {
+    Frame::new(ARROWS)
+}
+
+pub fn print_up_arrow() -> Frame {
+    Frame::new(UP_ARROW)
+}
+
+pub fn print_up_down_arrows() -> Frame {
+    Frame::new(UP_OR_DOWN)
+}
+
+pub fn print_pause() -> Frame {
+    Frame::new(PAUSE)
+}
+
+// Replaces the bottom row of the screen with a bar as long as the share of
+// the time that is left.
+pub fn print_time_bar(mut screen: Frame, time_left: Duration, total_time: Duration) -> Frame {
+    let total = total_time.as_millis().max(1);
+    let columns = (time_left.as_millis().min(total) * BOARD_SIZE as u32 + total - 1) / total;
+    let bar = !(0xff_u16 >> columns) as u8;
+    screen.set_row(BOARD_SIZE - 1, bar);
+    screen
+}
+
+pub fn print_setting_icon(setting: Setting) -> Frame {
+    match setting {
+        Setting::Rules       => Frame::new(RULES_ICON),
+        Setting::WallMode    => Frame::new(WALL_ICON),
+        Setting::Grace       => Frame::new(GRACE_ICON),
+        Setting::Brightness  => Frame::new(BRIGHTNESS_ICON),
+        Setting::Sound       => Frame::new(SOUND_ICON),
+        Setting::Speed       => Frame::new(SPEED_ICON),
+        Setting::Sensitivity => Frame::new(SENSITIVITY_ICON),
+    }
 }
 
-pub fn print_speed(speed: GameSpeed) -> [[BoardCell; BOARD_SIZE]; BOARD_SIZE] {
-    let speed_number = match speed {
-        GameSpeed::Slow   => ONE,
-        GameSpeed::Normal => TWO,
-        GameSpeed::Fast   => THREE,
+// The games played on the selected map are shown with the map itself.
+pub fn print_statistic_icon(statistic: Statistic, map_index: usize) -> Frame {
+    match statistic {
+        Statistic::GamesPlayed    => Frame::new(GAMES_ICON),
+        Statistic::MapGamesPlayed => print_map(map_index),
+        Statistic::Apples         => Frame::new(APPLE_ICON),
+        Statistic::LongestSnake   => Frame::new(LENGTH_ICON),
+        Statistic::PlayTime       => Frame::new(HOURGLASS_ICON),
+        Statistic::WallDeaths     => Frame::new(WALL_DEATH_ICON),
+        Statistic::ObstacleDeaths => Frame::new(OBSTACLE_DEATH_ICON),
+        Statistic::SnakeDeaths    => Frame::new(SNAKE_DEATH_ICON),
+        Statistic::Wins           => Frame::new(TROPHY),
+    }
+}
+
+pub fn print_game_mode_icon(mode: GameMode) -> Frame {
+    match mode {
+        GameMode::Classic    => Frame::new(CLASSIC_ICON),
+        GameMode::TimeAttack => Frame::new(TIME_ATTACK_ICON),
+        GameMode::Challenge  => Frame::new(CHALLENGE_ICON),
+    }
+}
+
+// The best scores of the game modes take turns, each of them after the icon
+// of its mode. Like a single score, the time for which they have been shown
+// picks what is visible.
+pub fn print_best_scores(best_scores: [u16; GAME_MODES_NUMBER], time_shown: Duration) -> Frame {
+    let page_duration = |score: u16| {
+        STATISTIC_ICON_DURATION + BEST_SCORE_DURATION.max(score_scroll_duration(score))
     };
-    print_lines(join_pictures(S, speed_number))
+    let cycle: u32 = best_scores.iter().map(|score| page_duration(*score).as_millis()).sum();
+
+    let mut time_in_page = Duration::from_millis(time_shown.as_millis() % cycle);
+    for &mode in GAME_MODES.iter() {
+        let score = best_scores[mode as usize];
+        if time_in_page >= page_duration(score) {
+            time_in_page = time_in_page - page_duration(score);
+        } else if time_in_page < STATISTIC_ICON_DURATION {
+            return print_game_mode_icon(mode);
+        } else {
+            return print_score(score, time_in_page - STATISTIC_ICON_DURATION);
+        }
+    }
+    Frame::default() // Shouldn't be reached.
 }
 
-pub fn print_trophy() -> [[BoardCell; BOARD_SIZE]; BOARD_SIZE] {
-    print_lines(TROPHY)
+pub fn print_error() -> Frame {
+    Frame::new(ERROR)
 }
 
-pub fn print_selection_arrows() -> [[BoardCell; BOARD_SIZE]; BOARD_SIZE] {
-    print_lines(ARROWS)
+pub fn print_map(index: usize) -> Frame {
+    Frame::new(MAPS[index])
 }
 
-pub fn print_up_down_arrows() -> [[BoardCell; BOARD_SIZE]; BOARD_SIZE] {
-    print_lines(UP_OR_DOWN)
+pub fn get_map_hazards(index: usize) -> &'static [Hazard] {
+    MAP_HAZARDS[index]
 }
 
-pub fn print_map(index: usize) -> [[BoardCell; BOARD_SIZE]; BOARD_SIZE] {
-    print_lines(MAPS[index])
+pub fn get_map_difficulty(index: usize) -> u8 {
+    MAP_DIFFICULTY[index]
 }
 
-fn print_lines(lines: [u8; BOARD_SIZE])
-    -> [[BoardCell; BOARD_SIZE]; BOARD_SIZE] {
+#[derive(Copy, Clone, PartialEq)]
+pub enum MenuChange {
+    CursorMoved,
+    EditingStarted,
+    ValueChanged,
+    EditingFinished,
+}
 
-    let mut screen: [[BoardCell; BOARD_SIZE]; BOARD_SIZE] = Default::default();
+/*
+ A scrollable menu of the items of a MenuModel. Up and down move between the
+ items, a short click starts editing the value of the current item which then
+ changes with up and down until the next short click. Both the items and the
+ values wrap around. Each push of the stick counts once, no matter how long
+ it is held.
 
-    /*
-     The user interface pictures are encoded using binary u8 numbers. A given
-     picture is represented by an array of eight unsigned integers, and each
-     of them represents a given row of the 8x8 matrix screen. In order to
-     turn that way of encoding pictures into a game board, we need to iterate
-     over rows and given each of those binary numbers we need to pass over it
-     using a "binary mask" which is essentially a power of 2 that we shift
-     leftwards. The way it works is as follows, suppose we wanto to print the
-     following row: 10110111, then in order to do it we need to find the bits
-     within that row which are set. That is achieved by taking the mask,
-     which at first is initialised to 00000001 and performing logical AND
-     of the row and the mask. If the result is non-zero, it means that the
-     row had the least significant bit set and we can mark that on the board.
-     To perform the next iteration, we multiply the mask by two which shifts
-     it to the left (logical shift left), then we perform AND and check the
-     result again. When shifting we need to check if the mask is below the
-     upper bound of the u8 to avoid overflow (it can happen after the last
-     iteration).
-    */
+ The icon of the current item takes up the top of the screen and its value is
+ shown as a bar along the bottom row, the bar is twice as thick while editing.
+*/
+pub struct MenuWidget {
+    cursor: usize,
+    is_editing: bool,
+    previous_direction: Direction,
+}
 
-    for i in 0..BOARD_SIZE {
-        for j in 0..BOARD_SIZE {
-            if is_bit_set(lines[i], j) {
-                screen[i][BOARD_SIZE - j - 1] = BoardCell::Obstacle;
+impl MenuWidget {
+    pub fn new() -> MenuWidget {
+        MenuWidget {
+            cursor: 0,
+            is_editing: false,
+            previous_direction: Direction::NoDirection,
+        }
+    }
+
+    pub fn stop_editing(&mut self) {
+        self.is_editing = false;
+    }
+
+    pub fn handle_input(&mut self, input: &ControllerInput, model: &mut dyn MenuModel)
+        -> Option<MenuChange> {
+
+        if input.is_button(ButtonEvent::ShortClick) {
+            self.is_editing = !self.is_editing;
+            return match self.is_editing {
+                true  => Some(MenuChange::EditingStarted),
+                false => Some(MenuChange::EditingFinished),
+            };
+        }
+
+        let direction = input.direction;
+        if direction == self.previous_direction {
+            return None;
+        }
+        self.previous_direction = direction;
+
+        // Up goes back in the list but increases the value.
+        let is_forward = match direction {
+            Direction::Up   => self.is_editing,
+            Direction::Down => !self.is_editing,
+            _               => return None,
+        };
+
+        if self.is_editing {
+            let count = model.value_count(self.cursor);
+            let value = step(model.value(self.cursor) as usize, count as usize, is_forward);
+            model.set_value(self.cursor, value as u8);
+            Some(MenuChange::ValueChanged)
+        } else {
+            self.cursor = step(self.cursor, model.len(), is_forward);
+            Some(MenuChange::CursorMoved)
+        }
+    }
+
+    pub fn render(&self, model: &dyn MenuModel) -> Frame {
+        let mut screen = model.icon(self.cursor);
+        for x in 0..=model.value(self.cursor) as usize {
+            screen.light_up(x, BOARD_SIZE - 1);
+            if self.is_editing {
+                screen.light_up(x, BOARD_SIZE - 2);
             }
         }
+        screen
     }
+}
 
-    screen
+/*
+ Pages through the lifetime statistics. Up and down move between them and
+ wrap around, each push of the stick counts once. Each statistic shows its
+ icon for a moment first and then its value, which scrolls like a score if
+ it is long.
+*/
+pub struct StatisticsBrowser {
+    cursor: usize,
+    previous_direction: Direction,
+    page_shown_at: Instant,
 }
 
-fn is_bit_set(number: u8, index: usize) -> bool {
-    number & 0b_1 << index as u8 != 0
+impl StatisticsBrowser {
+    pub fn new() -> StatisticsBrowser {
+        StatisticsBrowser {
+            cursor: 0,
+            previous_direction: Direction::NoDirection,
+            page_shown_at: Instant::default(),
+        }
+    }
+
+    // Shows the icon of the current statistic again.
+    pub fn restart(&mut self, now: Instant) {
+        self.page_shown_at = now;
+    }
+
+    // Returns whether it moved to another statistic.
+    pub fn handle_input(&mut self, input: &ControllerInput, now: Instant) -> bool {
+        let direction = input.direction;
+        if direction == self.previous_direction {
+            return false;
+        }
+        self.previous_direction = direction;
+
+        let is_forward = match direction {
+            Direction::Up   => false,
+            Direction::Down => true,
+            _               => return false,
+        };
+        self.cursor = step(self.cursor, STATISTICS.len(), is_forward);
+        self.page_shown_at = now;
+        true
+    }
+
+    pub fn render(&self, statistics: &Statistics, map_index: usize, speed: GameSpeed, now: Instant)
+        -> Frame {
+
+        let statistic = STATISTICS[self.cursor];
+        let time_shown = now - self.page_shown_at;
+        if time_shown < STATISTIC_ICON_DURATION {
+            return print_statistic_icon(statistic, map_index);
+        }
+
+        let value = statistics.value(statistic, map_index, speed).min(u16::MAX as u32);
+        print_score(value as u16, time_shown - STATISTIC_ICON_DURATION)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum CodeEntryChange {
+    DigitChanged,
+    CursorMoved,
+    Entered,
+}
+
+/*
+ Entry of the code of a challenge, one hexadecimal digit at a time. Up and
+ down change the digit under the cursor and wrap around, left and right move
+ the cursor. A short click moves on to the next digit and enters the code
+ after the last one. Each push of the stick counts once.
+
+ The digit under the cursor takes up the middle of the screen and the cursor
+ is a mark along the right edge, one step further down for each digit.
+*/
+pub struct CodeEntry {
+    digits: [u8; CODE_DIGITS],
+    cursor: usize,
+    previous_direction: Direction,
+}
+
+impl CodeEntry {
+    pub fn new() -> CodeEntry {
+        CodeEntry {
+            digits: [0; CODE_DIGITS],
+            cursor: 0,
+            previous_direction: Direction::NoDirection,
+        }
+    }
+
+    // The digits of the previous code are kept, so that it is quick to play
+    // the same challenge again.
+    pub fn restart(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn code(&self) -> u16 {
+        self.digits.iter().fold(0, |code, digit| (code << 4) | *digit as u16)
+    }
+
+    pub fn handle_input(&mut self, input: &ControllerInput) -> Option<CodeEntryChange> {
+        if input.is_button(ButtonEvent::ShortClick) {
+            if self.cursor + 1 == CODE_DIGITS {
+                return Some(CodeEntryChange::Entered);
+            }
+            self.cursor += 1;
+            return Some(CodeEntryChange::CursorMoved);
+        }
+
+        let direction = input.direction;
+        if direction == self.previous_direction {
+            return None;
+        }
+        self.previous_direction = direction;
+
+        let digit = &mut self.digits[self.cursor];
+        match direction {
+            Direction::Up    => *digit = step(*digit as usize, HEX_DIGITS, true) as u8,
+            Direction::Down  => *digit = step(*digit as usize, HEX_DIGITS, false) as u8,
+            Direction::Left  if self.cursor > 0 => {
+                self.cursor -= 1;
+                return Some(CodeEntryChange::CursorMoved);
+            },
+            Direction::Right if self.cursor + 1 < CODE_DIGITS => {
+                self.cursor += 1;
+                return Some(CodeEntryChange::CursorMoved);
+            },
+            _                => return None,
+        }
+        Some(CodeEntryChange::DigitChanged)
+    }
+
+    pub fn render(&self) -> Frame {
+        let mut screen = print_digit(self.digits[self.cursor]);
+        let rows_per_digit = BOARD_SIZE / CODE_DIGITS;
+        for y in self.cursor * rows_per_digit..(self.cursor + 1) * rows_per_digit {
+            screen.light_up(BOARD_SIZE - 1, y);
+        }
+        screen
+    }
+}
+
+fn step(index: usize, count: usize, is_forward: bool) -> usize {
+    match is_forward {
+        true  => (index + 1) % count,
+        false => (index + count - 1) % count,
+    }
 }
 
 fn join_pictures(first: [u8; BOARD_SIZE], second: [u8; BOARD_SIZE])
@@ -118,6 +521,209 @@ const UP_OR_DOWN: [u8; BOARD_SIZE] = [
 0b_00111100, //    111
 0b_00011000];//     1
 
+const UP_ARROW: [u8; BOARD_SIZE] = [
+0b_00011000, //    11
+0b_00111100, //   1111
+0b_01111110, //  111111
+0b_11011011, // 11 11 11
+0b_00011000, //    11
+0b_00011000, //    11
+0b_00011000, //    11
+0b_00011000];//    11
+
+const ERROR: [u8; BOARD_SIZE] = [
+0b_11111111, // 11111111
+0b_11000011, // 11    11
+0b_10100101, // 1 1  1 1
+0b_10011001, // 1  11  1
+0b_10011001, // 1  11  1
+0b_10100101, // 1 1  1 1
+0b_11000011, // 11    11
+0b_11111111];// 11111111
+
+const PAUSE: [u8; BOARD_SIZE] = [
+0b_00000000, //
+0b_01100110, //  11  11
+0b_01100110, //  11  11
+0b_01100110, //  11  11
+0b_01100110, //  11  11
+0b_01100110, //  11  11
+0b_01100110, //  11  11
+0b_00000000];//
+
+// The icons of the settings leave the two bottom rows free for the value.
+const RULES_ICON: [u8; BOARD_SIZE] = [
+0b_10111111, // 1 111111
+0b_00000000, //
+0b_10111111, // 1 111111
+0b_00000000, //
+0b_10111111, // 1 111111
+0b_00000000, //
+0b_00000000, //
+0b_00000000];//
+
+const WALL_ICON: [u8; BOARD_SIZE] = [
+0b_11111111, // 11111111
+0b_10010010, // 1  1  1
+0b_11111111, // 11111111
+0b_01001001, //  1  1  1
+0b_11111111, // 11111111
+0b_00000000, //
+0b_00000000, //
+0b_00000000];//
+
+const GRACE_ICON: [u8; BOARD_SIZE] = [
+0b_01100110, //  11  11
+0b_11111111, // 11111111
+0b_11111111, // 11111111
+0b_01111110, //  111111
+0b_00111100, //   1111
+0b_00011000, //    11
+0b_00000000, //
+0b_00000000];//
+
+const BRIGHTNESS_ICON: [u8; BOARD_SIZE] = [
+0b_10011001, // 1  11  1
+0b_01011010, //  1 11 1
+0b_00111100, //   1111
+0b_00111100, //   1111
+0b_01011010, //  1 11 1
+0b_10011001, // 1  11  1
+0b_00000000, //
+0b_00000000];//
+
+const SOUND_ICON: [u8; BOARD_SIZE] = [
+0b_00010010, //    1  1
+0b_00110001, //   11   1
+0b_11110101, // 1111 1 1
+0b_11110101, // 1111 1 1
+0b_00110001, //   11   1
+0b_00010010, //    1  1
+0b_00000000, //
+0b_00000000];//
+
+const SPEED_ICON: [u8; BOARD_SIZE] = [
+0b_10001000, // 1   1
+0b_01000100, //  1   1
+0b_00100010, //   1   1
+0b_00100010, //   1   1
+0b_01000100, //  1   1
+0b_10001000, // 1   1
+0b_00000000, //
+0b_00000000];//
+
+const SENSITIVITY_ICON: [u8; BOARD_SIZE] = [
+0b_00111100, //   1111
+0b_00111100, //   1111
+0b_00011000, //    11
+0b_00011000, //    11
+0b_01111110, //  111111
+0b_11111111, // 11111111
+0b_00000000, //
+0b_00000000];//
+
+// The icons of the game modes, shown with their best scores.
+const CLASSIC_ICON: [u8; BOARD_SIZE] = [
+0b_00000000, //
+0b_01111100, //  11111
+0b_01000000, //  1
+0b_01111100, //  11111
+0b_00000100, //      1
+0b_00000100, //      1
+0b_01111101, //  11111 1
+0b_00000000];//
+
+const TIME_ATTACK_ICON: [u8; BOARD_SIZE] = [
+0b_00111100, //   1111
+0b_01000010, //  1    1
+0b_10010001, // 1  1   1
+0b_10010001, // 1  1   1
+0b_10011101, // 1  111 1
+0b_10000001, // 1      1
+0b_01000010, //  1    1
+0b_00111100];//   1111
+
+const CHALLENGE_ICON: [u8; BOARD_SIZE] = [
+0b_00000000, //
+0b_01100000, //  11
+0b_10010000, // 1  1
+0b_10011111, // 1  11111
+0b_10010101, // 1  1 1 1
+0b_01100000, //  11
+0b_00000000, //
+0b_00000000];//
+
+// The icons of the statistics, the value follows on a screen of its own.
+const GAMES_ICON: [u8; BOARD_SIZE] = [
+0b_00000000, //
+0b_11110000, // 1111
+0b_00010000, //    1
+0b_00010000, //    1
+0b_00011110, //    1111
+0b_00000000, //
+0b_00000010, //       1
+0b_00000000];//
+
+const APPLE_ICON: [u8; BOARD_SIZE] = [
+0b_00001000, //     1
+0b_00010000, //    1
+0b_01101100, //  11 11
+0b_11111110, // 1111111
+0b_11111110, // 1111111
+0b_11111110, // 1111111
+0b_01111100, //  11111
+0b_00101000];//   1 1
+
+const LENGTH_ICON: [u8; BOARD_SIZE] = [
+0b_00000000, //
+0b_00000000, //
+0b_01000010, //  1    1
+0b_11111111, // 11111111
+0b_01000010, //  1    1
+0b_00000000, //
+0b_00000000, //
+0b_00000000];//
+
+const HOURGLASS_ICON: [u8; BOARD_SIZE] = [
+0b_11111111, // 11111111
+0b_01000010, //  1    1
+0b_00100100, //   1  1
+0b_00011000, //    11
+0b_00011000, //    11
+0b_00100100, //   1  1
+0b_01000010, //  1    1
+0b_11111111];// 11111111
+
+const WALL_DEATH_ICON: [u8; BOARD_SIZE] = [
+0b_00000011, //       11
+0b_00000011, //       11
+0b_00000011, //       11
+0b_11111011, // 11111 11
+0b_00000011, //       11
+0b_00000011, //       11
+0b_00000011, //       11
+0b_00000011];//       11
+
+const OBSTACLE_DEATH_ICON: [u8; BOARD_SIZE] = [
+0b_00000000, //
+0b_00000000, //
+0b_00001110, //     111
+0b_11101110, // 111 111
+0b_00001110, //     111
+0b_00000000, //
+0b_00000000, //
+0b_00000000];//
+
+const SNAKE_DEATH_ICON: [u8; BOARD_SIZE] = [
+0b_00000000, //
+0b_00111110, //   11111
+0b_00100010, //   1   1
+0b_00100010, //   1   1
+0b_11111110, // 1111111
+0b_00000000, //
+0b_00000000, //
+0b_00000000];//
+
 const ZERO: [u8; BOARD_SIZE] = [
 0b_0110, //  11
 0b_1001, // 1  1
@@ -218,20 +824,102 @@ const NINE: [u8; BOARD_SIZE] = [
 0b_1001, // 1  1
 0b_0110];//  11
 
-const S: [u8; BOARD_SIZE] = [
+const HEX_A: [u8; BOARD_SIZE] = [
+0b_0110, //  11
+0b_1001, // 1  1
+0b_1001, // 1  1
+0b_1111, // 1111
+0b_1001, // 1  1
+0b_1001, // 1  1
+0b_1001, // 1  1
+0b_1001];// 1  1
+
+const HEX_B: [u8; BOARD_SIZE] = [
+0b_1110, // 111
+0b_1001, // 1  1
+0b_1001, // 1  1
+0b_1110, // 111
+0b_1001, // 1  1
+0b_1001, // 1  1
+0b_1001, // 1  1
+0b_1110];// 111
+
+const HEX_C: [u8; BOARD_SIZE] = [
 0b_0110, //  11
 0b_1001, // 1  1
 0b_1000, // 1
-0b_0100, //  1
-0b_0010, //   1
-0b_0001, //    1
+0b_1000, // 1
+0b_1000, // 1
+0b_1000, // 1
 0b_1001, // 1  1
 0b_0110];//  11
 
-pub const MAPS_NUMBER: usize = 7;
+const HEX_D: [u8; BOARD_SIZE] = [
+0b_1110, // 111
+0b_1001, // 1  1
+0b_1001, // 1  1
+0b_1001, // 1  1
+0b_1001, // 1  1
+0b_1001, // 1  1
+0b_1001, // 1  1
+0b_1110];// 111
+
+const HEX_E: [u8; BOARD_SIZE] = [
+0b_1111, // 1111
+0b_1000, // 1
+0b_1000, // 1
+0b_1110, // 111
+0b_1000, // 1
+0b_1000, // 1
+0b_1000, // 1
+0b_1111];// 1111
+
+const HEX_F: [u8; BOARD_SIZE] = [
+0b_1111, // 1111
+0b_1000, // 1
+0b_1000, // 1
+0b_1110, // 111
+0b_1000, // 1
+0b_1000, // 1
+0b_1000, // 1
+0b_1000];// 1
+
+const DASH: [u8; BOARD_SIZE] = [
+0b_0000, //
+0b_0000, //
+0b_0000, //
+0b_0110, //  11
+0b_0110, //  11
+0b_0000, //
+0b_0000, //
+0b_0000];//
+
+pub const MAPS_NUMBER: usize = 10;
 
 const MAPS: [[u8; BOARD_SIZE]; MAPS_NUMBER] =
-[DEFAULT, SEVEN_BY_SEVEN, SIX_BY_SIX, FIVE_BY_FIVE, ZIG_ZAG, SQUEEZE, DOUGHNUT];
+[DEFAULT, SEVEN_BY_SEVEN, SIX_BY_SIX, FIVE_BY_FIVE, ZIG_ZAG, SQUEEZE, DOUGHNUT,
+ DEFAULT, GATES, DEFAULT];
+
+// The points scored on each map are weighted by its difficulty, in percent.
+const MAP_DIFFICULTY: [u8; MAPS_NUMBER] = [100, 110, 120, 140, 120, 120, 110, 140, 130, 140];
+
+// The moving parts of each map, on top of its static layout. They need to
+// stay clear of the left edge and the top row, where the snake starts.
+const MAP_HAZARDS: [&[Hazard]; MAPS_NUMBER] =
+[&[], &[], &[], &[], &[], &[], &[], &PATROLS, &GATE, &SHIFTING_WALL];
+
+const PATROLS: [Hazard; 2] = [
+    Hazard::Patrol { row: 3, from: 1, to: 7, step: Duration::from_millis(400) },
+    Hazard::Patrol { row: 5, from: 1, to: 6, step: Duration::from_millis(300) },
+];
+
+const GATE: [Hazard; 1] = [
+    Hazard::Gate { row: 5, columns: 0b_00011000, interval: Duration::from_secs(2) },
+];
+
+const SHIFTING_WALL: [Hazard; 1] = [
+    Hazard::ShiftingWall { columns: 0b_00111100, from_row: 2, to_row: 6, apples_per_shift: 3 },
+];
 
 const DEFAULT: [u8; BOARD_SIZE] = [
 0b_00000000,
@@ -302,3 +990,90 @@ const DOUGHNUT: [u8; BOARD_SIZE] = [
 0b_00000000,
 0b_00000000,
 0b_00000000];
+
+const GATES: [u8; BOARD_SIZE] = [
+0b_00000000,
+0b_00000000,
+0b_00000000,
+0b_00000000,
+0b_00000000,
+0b_01100111,
+0b_00000000,
+0b_00000000];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal_representation::hazard::hazard_reach;
+
+    fn at(millis: u32) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn short_scores_fit_on_the_screen() {
+        let forty_two = Frame::new(join_pictures(GLYPHS[4], GLYPHS[2]));
+        assert!(print_score(42, at(0)) == forty_two);
+        assert!(print_score(42, at(5000)) == forty_two);
+        assert!(score_scroll_duration(99) == Duration::ZERO);
+    }
+
+    #[test]
+    fn long_scores_scroll_through_all_their_digits() {
+        // The digits come in from the right edge of a blank screen.
+        let step = |steps: u32| SCROLL_STEP * steps;
+        assert!(print_score(123, at(0)) == Frame::default());
+        assert!(print_score(123, step(8)) == print_score(12, at(0)));
+        assert!(print_score(123, step(12)) == print_score(23, at(0)));
+        let three = Frame::new(join_pictures(GLYPHS[3], [0; BOARD_SIZE]));
+        assert!(print_score(123, step(16)) == three);
+
+        // The scroll takes long enough for the last digit to leave, then it
+        // starts over.
+        let duration = score_scroll_duration(123);
+        assert!(duration == step(20));
+        assert!(print_score(123, duration - SCROLL_STEP) != Frame::default());
+        assert!(print_score(123, duration) == Frame::default());
+        assert!(score_scroll_duration(12345) == step(28));
+    }
+
+    #[test]
+    fn best_scores_take_turns_after_the_icons() {
+        let best_scores = [12, 34, 56];
+
+        assert!(print_best_scores(best_scores, at(0)) == print_game_mode_icon(GameMode::Classic));
+        assert!(print_best_scores(best_scores, at(1000)) == print_score(12, at(0)));
+        assert!(print_best_scores(best_scores, at(3000)) == print_game_mode_icon(GameMode::TimeAttack));
+        assert!(print_best_scores(best_scores, at(4000)) == print_score(34, at(0)));
+        assert!(print_best_scores(best_scores, at(6000)) == print_game_mode_icon(GameMode::Challenge));
+        assert!(print_best_scores(best_scores, at(8999)) == print_score(56, at(0)));
+        assert!(print_best_scores(best_scores, at(9000)) == print_game_mode_icon(GameMode::Classic));
+    }
+
+    #[test]
+    fn long_best_scores_scroll_through_before_the_next_mode() {
+        let best_scores = [12345, 0, 0];
+        let scroll_end = STATISTIC_ICON_DURATION + score_scroll_duration(12345);
+        assert!(scroll_end > STATISTIC_ICON_DURATION + BEST_SCORE_DURATION);
+
+        let last_step = scroll_end - SCROLL_STEP;
+        assert!(print_best_scores(best_scores, last_step) ==
+            print_score(12345, last_step - STATISTIC_ICON_DURATION));
+        assert!(print_best_scores(best_scores, scroll_end) ==
+            print_game_mode_icon(GameMode::TimeAttack));
+    }
+
+    // The snake starts in the top left corner and goes down the left edge
+    // when it grows.
+    #[test]
+    fn hazards_stay_clear_of_the_start() {
+        for hazards in MAP_HAZARDS.iter() {
+            let reach = hazard_reach(hazards);
+            for i in 0..BOARD_SIZE {
+                assert!(!reach.is_lit(0, i));
+                assert!(!reach.is_lit(i, 0));
+            }
+        }
+    }
+}